@@ -18,5 +18,21 @@ fn standard_sudoku_bench(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, standard_sudoku_bench);
+/// a 17 clue puzzle, millions of nodes for the dfs.
+fn hard_sudoku_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hard");
+    group.sample_size(10);
+    group.bench_function("dfs solver", |b| {
+        b.iter(|| {
+            let input =
+                "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+            let mut board: sudoku_rs::board::Board = black_box(input).parse().unwrap();
+            let solver = sudoku_rs::solver::DfsSolver::new();
+            assert!(solver.solve(&mut board).is_ok());
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, standard_sudoku_bench, hard_sudoku_bench);
 criterion_main!(benches);
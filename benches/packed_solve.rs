@@ -0,0 +1,36 @@
+use criterion::*;
+use std::convert::TryFrom;
+use sudoku_rs::{
+    board::Board,
+    generator::Generator,
+    packed::{PackedBoard, PackedSolver},
+};
+
+fn packed_batch_bench(c: &mut Criterion) {
+    let mut g = Generator::new(42);
+    let puzzles: Vec<PackedBoard> = (0..100)
+        .map(|_| PackedBoard::try_from(&g.generate(&Board::new()).unwrap()).unwrap())
+        .collect();
+    let solver = PackedSolver::new();
+    let mut group = c.benchmark_group("packed");
+    group.throughput(Throughput::Elements(puzzles.len() as u64));
+    group.bench_function("solve batch", |b| {
+        b.iter(|| {
+            let mut boards = black_box(puzzles.clone());
+            for res in solver.solve_all(&mut boards) {
+                assert!(res.is_ok());
+            }
+        })
+    });
+    group.bench_function("unique batch", |b| {
+        b.iter(|| {
+            for board in black_box(&puzzles) {
+                assert!(solver.unique(board).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, packed_batch_bench);
+criterion_main!(benches);
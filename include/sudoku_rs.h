@@ -0,0 +1,161 @@
+#ifndef SUDOKU_RS_H
+#define SUDOKU_RS_H
+
+/* generated by cbindgen from src/capi.rs, do not edit. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * success.
+ */
+#define SUDOKU_OK 0
+
+/**
+ * `SuDoKuError::InvalidValue`.
+ */
+#define SUDOKU_INVALID_VALUE 1
+
+/**
+ * `SuDoKuError::OutOfBound`.
+ */
+#define SUDOKU_OUT_OF_BOUND 2
+
+/**
+ * `SuDoKuError::NotSolveable`.
+ */
+#define SUDOKU_NOT_SOLVEABLE 3
+
+/**
+ * `SuDoKuError::DuplicateValue`.
+ */
+#define SUDOKU_DUPLICATE_VALUE 4
+
+/**
+ * `SuDoKuError::BudgetExhausted`.
+ */
+#define SUDOKU_BUDGET_EXHAUSTED 5
+
+/**
+ * `SuDoKuError::InvalidFormat`.
+ */
+#define SUDOKU_INVALID_FORMAT 6
+
+/**
+ * `SuDoKuError::InvalidRegion`.
+ */
+#define SUDOKU_INVALID_REGION 7
+
+/**
+ * `SuDoKuError::InvalidCage`.
+ */
+#define SUDOKU_INVALID_CAGE 8
+
+/**
+ * a pointer argument is null.
+ */
+#define SUDOKU_NULL_POINTER 9
+
+/**
+ * the call panicked, which is a bug of this crate.
+ */
+#define SUDOKU_PANIC 10
+
+/**
+ * `SuDoKuError::GivensNotReached`.
+ */
+#define SUDOKU_GIVENS_NOT_REACHED 11
+
+/**
+ * board struct
+ */
+typedef struct Board Board;
+
+/**
+ * the score of `sudoku_grade`.
+ */
+typedef struct SudokuScore {
+  size_t dfs_complexity;
+  size_t empty_slot_count;
+} SudokuScore;
+
+/**
+ * return a new empty classic board.
+ */
+struct Board *sudoku_board_new(void);
+
+/**
+ * parse a board in the text format of `Board`, variants included.
+ *
+ * # Safety
+ * `text` is a nul terminated utf-8 string, `out` is null or points to a handle.
+ */
+int sudoku_board_parse(const char *text, struct Board **out);
+
+/**
+ * release a board, null is ignored.
+ *
+ * # Safety
+ * `board` is null or a handle not released yet.
+ */
+void sudoku_board_free(struct Board *board);
+
+/**
+ * write the 81 values of a board, 0 for empty.
+ *
+ * # Safety
+ * `board` is a handle, `cells` points to 81 bytes.
+ */
+int sudoku_board_cells(const struct Board *board, uint8_t *cells);
+
+/**
+ * return a board in the text format, release it with `sudoku_string_free`.
+ * null if `board` is null.
+ *
+ * # Safety
+ * `board` is null or a handle.
+ */
+char *sudoku_board_to_string(const struct Board *board);
+
+/**
+ * release a string of `sudoku_board_to_string`, null is ignored.
+ *
+ * # Safety
+ * `text` is null or a string of `sudoku_board_to_string` not released yet.
+ */
+void sudoku_string_free(char *text);
+
+/**
+ * solve a board in place, visit at most `max_nodes` nodes, 0 for unlimited.
+ *
+ * # Safety
+ * `board` is a handle.
+ */
+int sudoku_solve(struct Board *board, size_t max_nodes);
+
+/**
+ * check if the solve of a board is unique, the board is kept as it is.
+ *
+ * # Safety
+ * `board` is a handle, `unique` points to a bool.
+ */
+int sudoku_unique(const struct Board *board, size_t max_nodes, bool *unique);
+
+/**
+ * grade a board, the board is kept as it is.
+ *
+ * # Safety
+ * `board` is a handle, `score` points to a score.
+ */
+int sudoku_grade(const struct Board *board, size_t max_nodes, struct SudokuScore *score);
+
+/**
+ * generate a classic puzzle with a unique solve, reproducible with the same seed.
+ *
+ * # Safety
+ * `out` is null or points to a handle.
+ */
+int sudoku_generate(uint64_t seed, struct Board **out);
+
+#endif  /* SUDOKU_RS_H */
@@ -0,0 +1,358 @@
+//! the endpoints of the service, everything but the http.
+use serde_json::{json, Map, Value};
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use sudoku_rs::{
+    board::Board,
+    budget::Budget,
+    error::SuDoKuError,
+    generator::{Generator, JigsawOptions, KillerOptions},
+    grade::Grade,
+    solver::DfsSolver,
+};
+
+const ENDPOINTS: [&str; 7] = [
+    "solve", "unique", "grade", "hint", "generate", "health", "metrics",
+];
+
+/// the limits of every request, a request may ask for less.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub max_nodes: usize,
+    pub timeout: Duration,
+}
+
+impl Limits {
+    fn budget(&self, body: &Map<String, Value>) -> Result<Budget, Failure> {
+        let max_nodes = match body.get("max_nodes") {
+            Some(nodes) => nodes
+                .as_u64()
+                .ok_or_else(|| Failure::bad("max_nodes is not a number"))?
+                .min(self.max_nodes as u64) as usize,
+            None => self.max_nodes,
+        };
+        Ok(Budget::new()
+            .max_nodes(max_nodes)
+            .deadline(Instant::now() + self.timeout))
+    }
+}
+
+/// counters of the service, shared by the workers.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    requests: [AtomicU64; 7],
+    errors: [AtomicU64; 7],
+    micros: [AtomicU64; 7],
+    not_found: AtomicU64,
+    budget_exhausted: AtomicU64,
+}
+
+impl Metrics {
+    /// the prometheus text format.
+    fn render(&self) -> String {
+        let mut text = String::new();
+        let counters = [
+            ("sudoku_requests_total", "requests", &self.requests),
+            ("sudoku_errors_total", "requests which failed", &self.errors),
+        ];
+        for (name, help, counter) in counters.iter() {
+            let _ = writeln!(text, "# HELP {} {}.\n# TYPE {} counter", name, help, name);
+            for (endpoint, count) in ENDPOINTS.iter().zip(counter.iter()) {
+                let count = count.load(Ordering::Relaxed);
+                let _ = writeln!(text, "{}{{endpoint=\"{}\"}} {}", name, endpoint, count);
+            }
+        }
+        let name = "sudoku_request_seconds_total";
+        let _ = writeln!(
+            text,
+            "# HELP {} time spent on requests.\n# TYPE {} counter",
+            name, name
+        );
+        for (endpoint, micros) in ENDPOINTS.iter().zip(self.micros.iter()) {
+            let secs = micros.load(Ordering::Relaxed) as f64 / 1e6;
+            let _ = writeln!(text, "{}{{endpoint=\"{}\"}} {}", name, endpoint, secs);
+        }
+        let singles = [
+            (
+                "sudoku_not_found_total",
+                "requests to an unknown path",
+                &self.not_found,
+            ),
+            (
+                "sudoku_budget_exhausted_total",
+                "requests stopped by the node budget or the timeout",
+                &self.budget_exhausted,
+            ),
+        ];
+        for (name, help, count) in singles.iter() {
+            let count = count.load(Ordering::Relaxed);
+            let _ = writeln!(
+                text,
+                "# HELP {} {}.\n# TYPE {} counter\n{} {}",
+                name, help, name, name, count
+            );
+        }
+        text
+    }
+}
+
+/// the answer of a request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Reply {
+    pub fn json(status: u16, body: &Value) -> Self {
+        Reply {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Reply::json(status, &json!({ "error": message }))
+    }
+}
+
+/// why a request failed.
+#[derive(Debug)]
+enum Failure {
+    BadRequest(String),
+    SuDoKu(SuDoKuError),
+}
+
+impl Failure {
+    fn bad(message: &str) -> Self {
+        Failure::BadRequest(message.to_string())
+    }
+}
+
+impl From<SuDoKuError> for Failure {
+    fn from(err: SuDoKuError) -> Self {
+        Failure::SuDoKu(err)
+    }
+}
+
+/// the body of a post, a json object.
+fn object(body: &str) -> Result<Map<String, Value>, Failure> {
+    match serde_json::from_str(body) {
+        Ok(Value::Object(body)) => Ok(body),
+        Ok(_) => Err(Failure::bad("the body is not a json object")),
+        Err(e) => Err(Failure::BadRequest(e.to_string())),
+    }
+}
+
+/// the `board` field, in the text format of `Board`.
+fn board(body: &Map<String, Value>) -> Result<Board, Failure> {
+    let text = body
+        .get("board")
+        .and_then(Value::as_str)
+        .ok_or_else(|| Failure::bad("board is missing"))?;
+    Ok(text.parse()?)
+}
+
+fn solve(limits: &Limits, body: &Map<String, Value>) -> Result<Value, Failure> {
+    let mut board = board(body)?;
+    DfsSolver::with_budget(limits.budget(body)?).solve(&mut board)?;
+    Ok(json!({ "board": board.to_string() }))
+}
+
+fn unique(limits: &Limits, body: &Map<String, Value>) -> Result<Value, Failure> {
+    let mut board = board(body)?;
+    let unique = DfsSolver::with_budget(limits.budget(body)?).unique(&mut board)?;
+    Ok(json!({ "unique": unique }))
+}
+
+fn grade(limits: &Limits, body: &Map<String, Value>) -> Result<Value, Failure> {
+    let mut board = board(body)?;
+    let score = Grade::with_budget(limits.budget(body)?).try_grade(&mut board)?;
+    Ok(json!({
+        "dfs_complexity": score.dfs_complexity,
+        "empty_slot_count": score.empty_slot_count,
+    }))
+}
+
+fn hint(limits: &Limits, body: &Map<String, Value>) -> Result<Value, Failure> {
+    let board = board(body)?;
+    let hint = DfsSolver::with_budget(limits.budget(body)?).hint(&board)?;
+    Ok(json!({
+        "hint": hint.map(|(x, y, val)| json!({ "x": x, "y": y, "value": val })),
+    }))
+}
+
+/// a seed is picked from the clock if not given, it is in the reply to replay.
+fn generate(limits: &Limits, body: &Map<String, Value>) -> Result<Value, Failure> {
+    let seed = match body.get("seed") {
+        Some(seed) => seed
+            .as_u64()
+            .ok_or_else(|| Failure::bad("seed is not a number"))?,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0),
+    };
+    let variant = match body.get("variant") {
+        Some(variant) => variant
+            .as_str()
+            .ok_or_else(|| Failure::bad("variant is not a string"))?,
+        None => "classic",
+    };
+    let mut generator = Generator::new(seed).with_budget(limits.budget(body)?);
+    let board = match variant {
+        "classic" => generator.generate(&Board::new())?,
+        "diagonal" => generator.generate(&Board::new_diagonal())?,
+        "jigsaw" => generator.jigsaw(&JigsawOptions::new())?,
+        "killer" => generator.killer(&KillerOptions::new())?,
+        _ => return Err(Failure::bad("unknown variant")),
+    };
+    Ok(json!({ "board": board.to_string(), "seed": seed }))
+}
+
+/// answer a request, `path` may have a query.
+pub fn handle(limits: &Limits, metrics: &Metrics, method: &str, path: &str, body: &str) -> Reply {
+    let path = path.split('?').next().unwrap_or(path);
+    let index = match path
+        .strip_prefix('/')
+        .and_then(|name| ENDPOINTS.iter().position(|e| *e == name))
+    {
+        Some(index) => index,
+        None => {
+            metrics.not_found.fetch_add(1, Ordering::Relaxed);
+            return Reply::error(404, "not found");
+        }
+    };
+    let started = Instant::now();
+    let endpoint = ENDPOINTS[index];
+    if endpoint == "metrics" && method == "GET" {
+        metrics.requests[index].fetch_add(1, Ordering::Relaxed);
+        return Reply {
+            status: 200,
+            content_type: "text/plain; version=0.0.4",
+            body: metrics.render(),
+        };
+    }
+    let expected = match endpoint {
+        "health" | "metrics" => "GET",
+        _ => "POST",
+    };
+    let reply = if method != expected {
+        Reply::error(405, "method not allowed")
+    } else {
+        let res = match endpoint {
+            "health" => Ok(json!({ "status": "ok" })),
+            _ => object(body).and_then(|body| match endpoint {
+                "solve" => solve(limits, &body),
+                "unique" => unique(limits, &body),
+                "grade" => grade(limits, &body),
+                "hint" => hint(limits, &body),
+                _ => generate(limits, &body),
+            }),
+        };
+        match res {
+            Ok(value) => Reply::json(200, &value),
+            Err(Failure::BadRequest(message)) => Reply::error(400, &message),
+            Err(Failure::SuDoKu(err)) => {
+                if let SuDoKuError::BudgetExhausted = err {
+                    metrics.budget_exhausted.fetch_add(1, Ordering::Relaxed);
+                }
+                let status = match err {
+                    SuDoKuError::NotSolveable
+                    | SuDoKuError::BudgetExhausted
+                    | SuDoKuError::GivensNotReached => 422,
+                    _ => 400,
+                };
+                Reply::error(status, &err.to_string())
+            }
+        }
+    };
+    metrics.requests[index].fetch_add(1, Ordering::Relaxed);
+    if reply.status >= 400 {
+        metrics.errors[index].fetch_add(1, Ordering::Relaxed);
+    }
+    let micros = started.elapsed().as_micros() as u64;
+    metrics.micros[index].fetch_add(micros, Ordering::Relaxed);
+    reply
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EASY: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+
+    const LIMITS: Limits = Limits {
+        max_nodes: 1_000_000,
+        timeout: Duration::from_secs(10),
+    };
+
+    fn post(metrics: &Metrics, path: &str, body: &Value) -> (u16, Value) {
+        let reply = handle(&LIMITS, metrics, "POST", path, &body.to_string());
+        (reply.status, serde_json::from_str(&reply.body).unwrap())
+    }
+
+    #[test]
+    fn endpoints() {
+        let metrics = Metrics::default();
+        let (status, value) = post(&metrics, "/solve", &json!({ "board": EASY }));
+        assert_eq!(status, 200);
+        let solved: Board = value["board"].as_str().unwrap().parse().unwrap();
+        assert!(solved.is_win());
+        let (_, value) = post(&metrics, "/unique", &json!({ "board": EASY }));
+        assert_eq!(value["unique"], true);
+        let (_, value) = post(&metrics, "/grade", &json!({ "board": EASY }));
+        assert_eq!(value["empty_slot_count"], 54);
+        let (_, value) = post(&metrics, "/hint", &json!({ "board": EASY }));
+        assert!(value["hint"]["value"].is_u64());
+        let (_, value) = post(&metrics, "/hint", &json!({ "board": solved.to_string() }));
+        assert!(value["hint"].is_null());
+        let (status, value) = post(&metrics, "/generate", &json!({ "seed": 3 }));
+        assert_eq!(status, 200);
+        assert_eq!(value["seed"], 3);
+        let (_, again) = post(&metrics, "/generate", &json!({ "seed": 3 }));
+        assert_eq!(value, again);
+        let reply = handle(&LIMITS, &metrics, "GET", "/health", "");
+        assert_eq!(reply.status, 200);
+    }
+
+    #[test]
+    fn failures() {
+        let metrics = Metrics::default();
+        let (status, _) = post(&metrics, "/solve", &json!({ "board": "" , "max_nodes": 3 }));
+        assert_eq!(status, 422);
+        let (status, _) = post(&metrics, "/solve", &json!({ "board": "variant: nothing" }));
+        assert_eq!(status, 400);
+        let (status, _) = post(&metrics, "/grade", &json!({}));
+        assert_eq!(status, 400);
+        let (status, _) = post(&metrics, "/generate", &json!({ "variant": "samurai" }));
+        assert_eq!(status, 400);
+        for variant in ["classic", "jigsaw", "killer"].iter() {
+            let body = json!({ "variant": variant, "max_nodes": 1 });
+            let (status, _) = post(&metrics, "/generate", &body);
+            assert_eq!(status, 422);
+        }
+        let reply = handle(&LIMITS, &metrics, "POST", "/solve", "[");
+        assert_eq!(reply.status, 400);
+        let reply = handle(&LIMITS, &metrics, "GET", "/solve", "");
+        assert_eq!(reply.status, 405);
+        let reply = handle(&LIMITS, &metrics, "GET", "/nothing", "");
+        assert_eq!(reply.status, 404);
+        let reply = handle(&LIMITS, &metrics, "GET", "/metrics?x=1", "");
+        assert_eq!(reply.status, 200);
+        assert!(reply
+            .body
+            .contains("sudoku_errors_total{endpoint=\"solve\"} 4\n"));
+        assert!(reply
+            .body
+            .contains("sudoku_errors_total{endpoint=\"generate\"} 4\n"));
+        assert!(reply.body.contains("sudoku_budget_exhausted_total 4\n"));
+        assert!(reply.body.contains("sudoku_not_found_total 1\n"));
+    }
+}
@@ -0,0 +1,209 @@
+//! a json service over http, built with the `server` feature.
+//!
+//! ```text
+//! server [--addr ADDR] [--threads N] [--max-nodes N] [--timeout-ms N]
+//! ```
+//! boards are in the text format of `Board`, variants included.
+//!
+//! | endpoint         | body                                 | reply                                   |
+//! |------------------|--------------------------------------|-----------------------------------------|
+//! | `POST /solve`    | `{"board": "...", "max_nodes": 1000}` | `{"board": "..."}`                      |
+//! | `POST /unique`   | `{"board": "..."}`                   | `{"unique": true}`                      |
+//! | `POST /grade`    | `{"board": "..."}`                   | `{"dfs_complexity": 9, "empty_slot_count": 54}` |
+//! | `POST /hint`     | `{"board": "..."}`                   | `{"hint": {"x": 0, "y": 1, "value": 4}}`, `null` if full |
+//! | `POST /generate` | `{"seed": 3, "variant": "killer"}`   | `{"board": "...", "seed": 3}`           |
+//! | `GET /health`    |                                      | `{"status": "ok"}`                      |
+//! | `GET /metrics`   |                                      | counters in the prometheus text format  |
+//!
+//! every search runs under `--max-nodes` and `--timeout-ms`, a request may ask for fewer nodes.
+//! a failure is `{"error": "..."}`, with 400 for a bad request and 422 for a board without
+//! solve or out of budget.
+mod api;
+
+use api::{Limits, Metrics, Reply};
+use std::{
+    env,
+    io::{self, Read},
+    process,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+use tiny_http::{Header, Request, Response, Server};
+
+/// a bigger body is refused, no board is near it.
+const MAX_BODY: u64 = 64 * 1024;
+
+const USAGE: &str = "usage: server [--addr ADDR] [--threads N] [--max-nodes N] [--timeout-ms N]";
+
+struct Options {
+    addr: String,
+    threads: usize,
+    limits: Limits,
+}
+
+fn options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        addr: "127.0.0.1:8080".to_string(),
+        threads: 4,
+        limits: Limits {
+            max_nodes: 1_000_000,
+            timeout: Duration::from_secs(5),
+        },
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| USAGE.to_string())?;
+        let number = || value.parse::<u64>().map_err(|e| format!("{}: {}", arg, e));
+        match arg.as_str() {
+            "--addr" => options.addr = value.clone(),
+            "--threads" => options.threads = number()?.max(1) as usize,
+            "--max-nodes" => options.limits.max_nodes = number()? as usize,
+            "--timeout-ms" => options.limits.timeout = Duration::from_millis(number()?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    Ok(options)
+}
+
+fn respond(request: Request, reply: Reply) -> io::Result<()> {
+    let header = Header::from_bytes("Content-Type", reply.content_type)
+        .expect("content types are valid headers");
+    let response = Response::from_string(reply.body)
+        .with_status_code(reply.status)
+        .with_header(header);
+    request.respond(response)
+}
+
+fn answer(limits: &Limits, metrics: &Metrics, mut request: Request) -> io::Result<()> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_end(&mut body)?;
+    let reply = if body.len() as u64 > MAX_BODY {
+        Reply::error(413, "the body is too large")
+    } else {
+        match String::from_utf8(body) {
+            Ok(body) => {
+                let method = request.method().to_string();
+                api::handle(limits, metrics, &method, request.url(), &body)
+            }
+            Err(_) => Reply::error(400, "the body is not utf-8"),
+        }
+    };
+    respond(request, reply)
+}
+
+/// answer requests on `threads` workers, return once every worker is done.
+fn serve(server: Arc<Server>, limits: Limits, metrics: Arc<Metrics>, threads: usize) {
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let server = server.clone();
+            let metrics = metrics.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    // the client is gone, nothing to do
+                    let _ = answer(&limits, &metrics, request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match options(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    let server = match Server::http(&options.addr) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("{}: {}", options.addr, e);
+            process::exit(1);
+        }
+    };
+    eprintln!("listening on {}", server.server_addr());
+    serve(
+        Arc::new(server),
+        options.limits,
+        Arc::new(Metrics::default()),
+        options.threads,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{io::Write, net::TcpStream};
+    use sudoku_rs::{board::Board, solver::DfsSolver};
+
+    const EASY: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+
+    fn request(addr: &str, head: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{}\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            head,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        reply
+    }
+
+    #[test]
+    fn localhost() {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let addr = server.server_addr().to_string();
+        let limits = Limits {
+            max_nodes: 1_000,
+            timeout: Duration::from_secs(10),
+        };
+        let metrics = Arc::new(Metrics::default());
+        thread::spawn(move || serve(server, limits, metrics, 2));
+
+        let mut solved: Board = EASY.parse().unwrap();
+        DfsSolver::new().solve(&mut solved).unwrap();
+        let body = format!("{{\"board\": {:?}}}", solved.to_string());
+        let reply = request(&addr, "POST /unique HTTP/1.1", &body);
+        assert!(reply.starts_with("HTTP/1.1 200"));
+        assert!(reply.contains("application/json"));
+        assert!(reply.ends_with("{\"unique\":true}"));
+        // the cap of the server wins over the request
+        let body = format!("{{\"board\": \"{}\", \"max_nodes\": 1000000}}", EASY);
+        let reply = request(&addr, "POST /solve HTTP/1.1", &body);
+        assert!(reply.starts_with("HTTP/1.1 422"));
+        assert!(reply.contains("BudgetExhausted"));
+        let big = " ".repeat(MAX_BODY as usize + 1);
+        let reply = request(&addr, "POST /grade HTTP/1.1", &big);
+        assert!(reply.starts_with("HTTP/1.1 413"));
+        let reply = request(&addr, "GET /health HTTP/1.1", "");
+        assert!(reply.ends_with("{\"status\":\"ok\"}"));
+        let reply = request(&addr, "GET /metrics HTTP/1.1", "");
+        assert!(reply.contains("sudoku_requests_total{endpoint=\"unique\"} 1\n"));
+        assert!(reply.contains("sudoku_budget_exhausted_total 1\n"));
+    }
+
+    #[test]
+    fn bad_options() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|a| a.to_string()).collect() };
+        assert!(options(&args(&["--threads"])).is_err());
+        assert!(options(&args(&["--max-nodes", "many"])).is_err());
+        assert!(options(&args(&["--port", "80"])).is_err());
+        let parsed = options(&args(&["--threads", "0", "--timeout-ms", "20"])).unwrap();
+        assert_eq!(parsed.threads, 1);
+        assert_eq!(parsed.limits.timeout, Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,275 @@
+//! the state of a game, everything but the terminal.
+use std::time::{Duration, Instant};
+use sudoku_rs::{bitset::CandidateSet, board::Board, error::SuDoKuError, solver::DfsSolver};
+
+/// a slot before a change, for undo.
+#[derive(Clone, Copy, Debug)]
+struct Change {
+    pos: usize,
+    val: Option<u8>,
+    marks: CandidateSet,
+}
+
+pub struct Game {
+    /// the givens, never changed.
+    puzzle: Board,
+    /// the values put by the player.
+    entries: [Option<u8>; 81],
+    /// the givens and the entries, built again after every change.
+    /// a `Board` can not take a value away from a slot with a conflict.
+    board: Board,
+    solution: Board,
+    marks: [CandidateSet; 81],
+    cursor: (usize, usize),
+    pencil: bool,
+    undo: Vec<Change>,
+    started: Instant,
+    finished: Option<Duration>,
+    status: String,
+}
+
+impl Game {
+    /// start a game, fail if the puzzle has no solve.
+    pub fn new(puzzle: Board) -> Result<Self, SuDoKuError> {
+        let mut solution = puzzle.clone();
+        DfsSolver::new().solve(&mut solution)?;
+        Ok(Game {
+            board: puzzle.clone(),
+            puzzle,
+            entries: [None; 81],
+            solution,
+            marks: [CandidateSet::new(); 81],
+            cursor: (0, 0),
+            pencil: false,
+            undo: Vec::new(),
+            started: Instant::now(),
+            finished: None,
+            status: String::new(),
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    pub fn pencil(&self) -> bool {
+        self.pencil
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn marks(&self, x: usize, y: usize) -> CandidateSet {
+        self.marks[x * 9 + y]
+    }
+
+    pub fn is_given(&self, x: usize, y: usize) -> bool {
+        !self.puzzle.is_empty(x, y)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// time spent, it stops once the board is solved.
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// move the cursor, wrap around the edges.
+    pub fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let (x, y) = self.cursor;
+        self.cursor = (
+            (x as isize + dx).rem_euclid(9) as usize,
+            (y as isize + dy).rem_euclid(9) as usize,
+        );
+    }
+
+    pub fn toggle_pencil(&mut self) {
+        self.pencil = !self.pencil;
+    }
+
+    /// put a value on the cursor, or toggle a mark in pencil mode.
+    pub fn input(&mut self, val: u8) {
+        let (x, y) = self.cursor;
+        if self.is_given(x, y) || self.is_finished() {
+            return;
+        }
+        self.save(x, y);
+        if self.pencil {
+            let marks = self.marks[x * 9 + y];
+            self.marks[x * 9 + y] = marks ^ CandidateSet::single(val);
+        } else {
+            self.entries[x * 9 + y] = Some(val);
+            self.board = self.build(None);
+            self.check_finished();
+        }
+    }
+
+    /// clear the value and marks of the cursor.
+    pub fn clear(&mut self) {
+        let (x, y) = self.cursor;
+        if self.is_given(x, y) || self.is_finished() {
+            return;
+        }
+        self.save(x, y);
+        self.entries[x * 9 + y] = None;
+        self.marks[x * 9 + y] = CandidateSet::new();
+        self.board = self.build(None);
+    }
+
+    pub fn undo(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+        match self.undo.pop() {
+            Some(change) => {
+                self.entries[change.pos] = change.val;
+                self.marks[change.pos] = change.marks;
+                self.board = self.build(None);
+                self.cursor = (change.pos / 9, change.pos % 9);
+                self.status.clear();
+            }
+            None => self.status = "nothing to undo".to_string(),
+        }
+    }
+
+    /// fill the slot the solver would fill next.
+    /// with a wrong value on the board, move to it instead.
+    pub fn hint(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+        match DfsSolver::new().hint(&self.board) {
+            Ok(Some((x, y, val))) => {
+                self.cursor = (x, y);
+                self.save(x, y);
+                self.entries[x * 9 + y] = Some(val);
+                self.board = self.build(None);
+                self.status = format!("hint: r{}c{} is {}", x + 1, y + 1, val);
+                self.check_finished();
+            }
+            // full or no solve, so some value is wrong
+            _ => match (0..81).find(|&pos| self.is_wrong(pos / 9, pos % 9)) {
+                Some(pos) => {
+                    self.cursor = (pos / 9, pos % 9);
+                    self.status = "no solve from here, this value is wrong".to_string();
+                }
+                None => self.status = "no solve from here".to_string(),
+            },
+        }
+    }
+
+    /// the value on a slot breaks a rule with the other values.
+    pub fn conflicts(&self, x: usize, y: usize) -> bool {
+        match self.board.unchecked_get(x, y) {
+            Some(val) => !self
+                .build(Some(x * 9 + y))
+                .candidate_set(x, y)
+                .contains(val),
+            None => false,
+        }
+    }
+
+    /// the givens and the entries but the one on `skip`.
+    fn build(&self, skip: Option<usize>) -> Board {
+        let mut board = self.puzzle.clone();
+        for (pos, val) in self.entries.iter().enumerate() {
+            match *val {
+                Some(val) if Some(pos) != skip => board.unchecked_set(pos / 9, pos % 9, val),
+                _ => {}
+            }
+        }
+        board
+    }
+
+    fn is_wrong(&self, x: usize, y: usize) -> bool {
+        let entry = self.entries[x * 9 + y];
+        entry.is_some() && entry != self.solution.unchecked_get(x, y)
+    }
+
+    fn save(&mut self, x: usize, y: usize) {
+        self.undo.push(Change {
+            pos: x * 9 + y,
+            val: self.entries[x * 9 + y],
+            marks: self.marks[x * 9 + y],
+        });
+        self.status.clear();
+    }
+
+    fn check_finished(&mut self) {
+        let full = (0..81).all(|pos| !self.board.is_empty(pos / 9, pos % 9));
+        if !full || (0..81).any(|pos| self.conflicts(pos / 9, pos % 9)) {
+            return;
+        }
+        // cages and constraints
+        if self.board.is_win() {
+            let elapsed = self.started.elapsed();
+            self.finished = Some(elapsed);
+            self.status = format!(
+                "solved in {}:{:02}",
+                elapsed.as_secs() / 60,
+                elapsed.as_secs() % 60
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EASY: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+
+    #[test]
+    fn input_and_undo() {
+        let mut game = Game::new(EASY.parse().unwrap()).unwrap();
+        // (0, 0) is given
+        game.input(5);
+        assert_eq!(game.board().unchecked_get(0, 0), Some(2));
+        game.move_cursor(0, 1);
+        game.input(2);
+        assert!(game.conflicts(0, 1));
+        game.input(5);
+        assert!(!game.conflicts(0, 1));
+        game.toggle_pencil();
+        game.input(4);
+        game.input(6);
+        game.input(4);
+        assert_eq!(game.marks(0, 1), CandidateSet::single(6));
+        game.undo();
+        game.undo();
+        game.undo();
+        assert!(game.marks(0, 1).is_empty());
+        game.undo();
+        assert_eq!(game.board().unchecked_get(0, 1), Some(2));
+        game.clear();
+        assert!(game.board().is_empty(0, 1));
+        game.move_cursor(-1, -2);
+        assert_eq!(game.cursor(), (8, 8));
+    }
+
+    #[test]
+    fn hint() {
+        let mut game = Game::new(EASY.parse().unwrap()).unwrap();
+        game.move_cursor(0, 1);
+        game.input(5);
+        game.input(4);
+        game.hint();
+        // a wrong value, the hint points at it
+        assert_eq!(game.cursor(), (0, 1));
+        game.undo();
+        game.undo();
+        for _ in 0..54 {
+            game.hint();
+        }
+        assert!(game.is_finished());
+        assert!(game.status().starts_with("solved"));
+    }
+}
@@ -0,0 +1,147 @@
+//! a sudoku game in the terminal, built with the `tui` feature.
+//!
+//! ```text
+//! tui [FILE] [--line N] [--seed N] [--variant classic|diagonal|jigsaw|killer]
+//! ```
+//! a puzzle is read from `FILE` in the text format of `Board`, `--line` picks one puzzle
+//! of a file with one puzzle per line. without a file a puzzle is generated.
+mod game;
+mod view;
+
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use game::Game;
+use std::{
+    env, fs,
+    io::{self, Write},
+    panic, process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use sudoku_rs::{
+    board::Board,
+    generator::{Generator, JigsawOptions, KillerOptions},
+};
+
+/// how often the timer is redrawn without input.
+const TICK: Duration = Duration::from_millis(250);
+
+const USAGE: &str =
+    "usage: tui [FILE] [--line N] [--seed N] [--variant classic|diagonal|jigsaw|killer]";
+
+/// read or generate the puzzle of the command line.
+fn puzzle(args: &[String]) -> Result<Board, String> {
+    let mut file = None;
+    let mut line = None;
+    let mut seed = None;
+    let mut variant = "classic".to_string();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| USAGE.to_string());
+        match arg.as_str() {
+            "--line" => line = Some(value()?.parse::<usize>().map_err(|e| e.to_string())?),
+            "--seed" => seed = Some(value()?.parse::<u64>().map_err(|e| e.to_string())?),
+            "--variant" => variant = value()?.clone(),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if file.is_none() && !arg.starts_with('-') => file = Some(arg.clone()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    if let Some(file) = file {
+        let text = fs::read_to_string(&file).map_err(|e| format!("{}: {}", file, e))?;
+        let text = match line {
+            Some(line) => text
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .nth(line)
+                .ok_or_else(|| format!("{}: no line {}", file, line))?
+                .to_string(),
+            None => text,
+        };
+        return text.parse().map_err(|e| format!("{}: {}", file, e));
+    }
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    });
+    let mut generator = Generator::new(seed);
+    let board = match variant.as_str() {
+        "classic" => generator.generate(&Board::new()),
+        "diagonal" => generator.generate(&Board::new_diagonal()),
+        "jigsaw" => generator.jigsaw(&JigsawOptions::new()),
+        "killer" => generator.killer(&KillerOptions::new()),
+        _ => return Err(USAGE.to_string()),
+    };
+    board.map_err(|e| e.to_string())
+}
+
+fn restore() -> io::Result<()> {
+    execute!(io::stdout(), Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+fn run<W: Write>(game: &mut Game, out: &mut W) -> io::Result<()> {
+    loop {
+        view::draw(game, out)?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            _ => continue,
+        };
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => game.move_cursor(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => game.move_cursor(1, 0),
+            KeyCode::Left | KeyCode::Char('h') => game.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => game.move_cursor(0, 1),
+            KeyCode::Char(ch @ '1'..='9') => game.input(ch as u8 - b'0'),
+            KeyCode::Char('0') | KeyCode::Char('.') | KeyCode::Backspace | KeyCode::Delete => {
+                game.clear()
+            }
+            KeyCode::Char('p') => game.toggle_pencil(),
+            KeyCode::Char('u') => game.undo(),
+            KeyCode::Char('?') => game.hint(),
+            _ => {}
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut game = match puzzle(&args)
+        .and_then(|board| Game::new(board).map_err(|_| "the puzzle has no solve".to_string()))
+    {
+        Ok(game) => game,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    // leave the alternate screen before a panic message is printed
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        hook(info);
+    }));
+    let mut out = io::stdout();
+    let res = terminal::enable_raw_mode()
+        .and_then(|_| execute!(out, EnterAlternateScreen, Hide))
+        .and_then(|_| run(&mut game, &mut out));
+    let _ = restore();
+    if let Err(e) = res {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+    if game.is_finished() {
+        println!("{}", game.status());
+    }
+}
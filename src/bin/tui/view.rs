@@ -0,0 +1,157 @@
+//! draw a game on the terminal.
+use crate::game::Game;
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write};
+
+/// where the side panel starts.
+const PANEL: u16 = 40;
+
+/// a vertical line left of the slot `(x, y)`, `y` may be 9 for the right edge.
+fn vertical(game: &Game, x: usize, y: usize) -> bool {
+    y == 0 || y == 9 || game.board().region(x, y - 1) != game.board().region(x, y)
+}
+
+/// a horizontal line above the slot `(x, y)`, `x` may be 9 for the bottom edge.
+fn horizontal(game: &Game, x: usize, y: usize) -> bool {
+    x == 0 || x == 9 || game.board().region(x - 1, y) != game.board().region(x, y)
+}
+
+/// the box drawing char where four slots meet, `(x, y)` is the slot below right.
+fn junction(game: &Game, x: usize, y: usize) -> char {
+    let up = x > 0 && vertical(game, x - 1, y);
+    let down = x < 9 && vertical(game, x, y);
+    let left = y > 0 && horizontal(game, x, y - 1);
+    let right = y < 9 && horizontal(game, x, y);
+    match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (_, _, false, false) => '│',
+        (false, false, _, _) => '─',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+fn draw_slot<W: Write>(game: &Game, out: &mut W, x: usize, y: usize) -> io::Result<()> {
+    if game.cursor() == (x, y) {
+        queue!(out, SetAttribute(Attribute::Reverse))?;
+    }
+    match game.board().unchecked_get(x, y) {
+        Some(val) => {
+            if game.conflicts(x, y) {
+                queue!(out, SetForegroundColor(Color::Red))?;
+            } else if game.is_given(x, y) {
+                queue!(out, SetAttribute(Attribute::Bold))?;
+            } else {
+                queue!(out, SetForegroundColor(Color::Cyan))?;
+            }
+            queue!(out, Print(format!(" {} ", val)))?;
+        }
+        // pencil marks are listed in the panel
+        None if !game.marks(x, y).is_empty() => {
+            queue!(out, SetForegroundColor(Color::DarkGrey), Print(" · "))?
+        }
+        None => queue!(out, Print("   "))?,
+    }
+    queue!(out, SetAttribute(Attribute::Reset), ResetColor)
+}
+
+fn draw_board<W: Write>(game: &Game, out: &mut W) -> io::Result<()> {
+    for x in 0..=9 {
+        // the line above row x
+        let mut line = String::new();
+        for y in 0..=9 {
+            line.push(junction(game, x, y));
+            if y < 9 {
+                line.push_str(if horizontal(game, x, y) {
+                    "───"
+                } else {
+                    "   "
+                });
+            }
+        }
+        queue!(out, MoveTo(0, 2 * x as u16), Print(line))?;
+        if x == 9 {
+            break;
+        }
+        queue!(out, MoveTo(0, 2 * x as u16 + 1))?;
+        for y in 0..9 {
+            queue!(out, Print(if vertical(game, x, y) { '│' } else { ' ' }))?;
+            draw_slot(game, out, x, y)?;
+        }
+        queue!(out, Print('│'))?;
+    }
+    Ok(())
+}
+
+fn draw_panel<W: Write>(game: &Game, out: &mut W) -> io::Result<()> {
+    let (x, y) = game.cursor();
+    let secs = game.elapsed().as_secs();
+    let marks: Vec<String> = game.marks(x, y).iter().map(|m| m.to_string()).collect();
+    let mut lines = vec![
+        format!("time   {}:{:02}", secs / 60, secs % 60),
+        format!("mode   {}", if game.pencil() { "pencil" } else { "value" }),
+        format!("slot   r{}c{}", x + 1, y + 1),
+        format!("marks  {}", marks.join(" ")),
+    ];
+    if let Some(cage) = game
+        .board()
+        .cages()
+        .iter()
+        .find(|cage| cage.slots().contains(&(x, y)))
+    {
+        lines.push(format!("cage   {} in {}", cage.sum(), cage.slots().len()));
+    }
+    lines.push(String::new());
+    lines.extend(
+        [
+            "arrows, hjkl  move",
+            "1-9           put a value",
+            "0, backspace  clear",
+            "p             pencil marks",
+            "u             undo",
+            "?             hint",
+            "q             quit",
+        ]
+        .iter()
+        .map(|line| line.to_string()),
+    );
+    // the other rules, a game can not be played without them
+    let rules: Vec<String> = game
+        .board()
+        .constraints()
+        .iter()
+        .filter_map(|c| c.directive())
+        .collect();
+    if game.board().is_diagonal() || !rules.is_empty() {
+        lines.push(String::new());
+    }
+    if game.board().is_diagonal() {
+        lines.push("variant: diagonal".to_string());
+    }
+    lines.extend(rules);
+    for (row, line) in lines.iter().enumerate() {
+        queue!(out, MoveTo(PANEL, row as u16), Print(line))?;
+    }
+    Ok(())
+}
+
+/// draw the whole screen.
+pub fn draw<W: Write>(game: &Game, out: &mut W) -> io::Result<()> {
+    queue!(out, Clear(ClearType::All))?;
+    draw_board(game, out)?;
+    draw_panel(game, out)?;
+    queue!(out, MoveTo(0, 20), Print(game.status()))?;
+    out.flush()
+}
@@ -1,16 +1,52 @@
+//! a small set of values, one bit per value.
+//!
+//! the keys can be `0..16`, sudoku only use `1..=9`.
+//! ```
+//! use sudoku_rs::bitset::CandidateSet;
+//!
+//! let a: CandidateSet = [1, 2, 3, 5].iter().cloned().collect();
+//! let b: CandidateSet = [3, 4, 5].iter().cloned().collect();
+//! assert_eq!((a & b).iter().collect::<Vec<_>>(), vec![3, 5]);
+//! assert_eq!((a - b).iter().collect::<Vec<_>>(), vec![1, 2]);
+//! assert_eq!(a.complement().iter().collect::<Vec<_>>(), vec![4, 6, 7, 8, 9]);
+//! assert_eq!((a & b).max(), Some(5));
+//! assert_eq!((a - b - CandidateSet::single(1)).only(), Some(2));
+//! ```
 use crate::error::*;
-use std::ops::BitOr;
+use core::{
+    fmt,
+    iter::FromIterator,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not, RangeInclusive, Sub, SubAssign},
+};
 
-#[derive(Default, Copy, Clone)]
-pub(crate) struct BitSet {
+/// the values `1..=9`.
+const FULL: u16 = 0b11_1111_1110;
+
+/// a set of values.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CandidateSet {
     inner: u16,
 }
 
-impl BitSet {
+impl CandidateSet {
+    /// return an empty set.
     pub fn new() -> Self {
-        BitSet { inner: 0 }
+        CandidateSet { inner: 0 }
+    }
+
+    /// return the set of `1..=9`.
+    pub fn full() -> Self {
+        CandidateSet { inner: FULL }
+    }
+
+    /// return a set of one value.
+    pub fn single(key: u8) -> Self {
+        let mut set = CandidateSet::new();
+        set.insert(key);
+        set
     }
 
+    /// put a key in set, fail if the key is out of `0..16`.
     pub fn set(&mut self, key: u8) -> Result<(), SuDoKuError> {
         if key >= 16 {
             return Err(SuDoKuError::OutOfBound);
@@ -19,6 +55,17 @@ impl BitSet {
         Ok(())
     }
 
+    /// return a set from its bits, bit `i` for key `i`.
+    pub fn from_bits(inner: u16) -> Self {
+        CandidateSet { inner }
+    }
+
+    /// the bits of set, bit `i` for key `i`.
+    pub fn bits(self) -> u16 {
+        self.inner
+    }
+
+    /// check if a key is in set, fail if the key is out of `0..16`.
     pub fn get(self, key: u8) -> Result<bool, SuDoKuError> {
         if key >= 16 {
             return Err(SuDoKuError::OutOfBound);
@@ -26,34 +73,283 @@ impl BitSet {
         Ok(self.inner & (1 << key) != 0)
     }
 
+    /// put a key in set, return if it was not in set.
+    /// panics if the key is out of `0..16`.
+    pub fn insert(&mut self, key: u8) -> bool {
+        assert!(key < 16, "bitset: key out of bound");
+        self.unchecked_insert(key)
+    }
+
+    /// check if a key is in set.
+    /// panics if the key is out of `0..16`.
+    pub fn contains(self, key: u8) -> bool {
+        assert!(key < 16, "bitset: key out of bound");
+        self.unchecked_contains(key)
+    }
+
+    /// take a key out of set, return if it was in set.
+    /// panics if the key is out of `0..16`.
+    pub fn discard(&mut self, key: u8) -> bool {
+        assert!(key < 16, "bitset: key out of bound");
+        self.unchecked_discard(key)
+    }
+
+    /// `insert` for the hot paths, the caller keeps the key in `0..16`.
+    pub(crate) fn unchecked_insert(&mut self, key: u8) -> bool {
+        debug_assert!(key < 16, "bitset: key out of bound");
+        let before = self.unchecked_contains(key);
+        self.inner |= 1 << key;
+        !before
+    }
+
+    /// `contains` for the hot paths, the caller keeps the key in `0..16`.
+    pub(crate) fn unchecked_contains(self, key: u8) -> bool {
+        debug_assert!(key < 16, "bitset: key out of bound");
+        self.inner & (1 << key) != 0
+    }
+
+    /// `discard` for the hot paths, the caller keeps the key in `0..16`.
+    pub(crate) fn unchecked_discard(&mut self, key: u8) -> bool {
+        debug_assert!(key < 16, "bitset: key out of bound");
+        let before = self.unchecked_contains(key);
+        self.inner &= !(1 << key);
+        before
+    }
+
+    /// how many keys in set.
     pub fn count(self) -> usize {
         self.inner.count_ones() as usize
     }
 
+    /// check if set is empty.
+    pub fn is_empty(self) -> bool {
+        self.inner == 0
+    }
+
+    /// sum of the keys in set.
+    pub fn sum(self) -> u8 {
+        self.iter().sum()
+    }
+
+    /// take a key out of set, fail if the key is out of `0..16`.
     pub fn remove(&mut self, key: u8) -> Result<bool, SuDoKuError> {
         let before = self.get(key)?;
         self.inner &= !(1 << key);
         Ok(before)
     }
 
-    pub fn reverse(self, range: std::ops::Range<u8>) -> Vec<u8> {
-        let mut res = Vec::new();
-        for i in range {
-            if !self.get(i).unwrap_or(true) {
-                res.push(i);
-            }
+    /// the keys in both sets.
+    pub fn intersection(self, other: Self) -> Self {
+        CandidateSet {
+            inner: self.inner & other.inner,
+        }
+    }
+
+    /// the keys in either set.
+    pub fn union(self, other: Self) -> Self {
+        CandidateSet {
+            inner: self.inner | other.inner,
+        }
+    }
+
+    /// the keys in set but not in `other`.
+    pub fn difference(self, other: Self) -> Self {
+        CandidateSet {
+            inner: self.inner & !other.inner,
+        }
+    }
+
+    /// the keys in exactly one of the sets.
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        CandidateSet {
+            inner: self.inner ^ other.inner,
+        }
+    }
+
+    /// check if every key of set is in `other`.
+    pub fn is_subset(self, other: Self) -> bool {
+        self.inner & !other.inner == 0
+    }
+
+    /// the keys in `1..=9` which are not in set.
+    pub fn complement(self) -> Self {
+        CandidateSet {
+            inner: !self.inner & FULL,
+        }
+    }
+
+    /// the keys in `range` which are not in set.
+    /// ```
+    /// use sudoku_rs::bitset::CandidateSet;
+    ///
+    /// let set = CandidateSet::single(2);
+    /// assert_eq!(set.complement_in(1..=4).iter().collect::<Vec<_>>(), vec![1, 3, 4]);
+    /// ```
+    pub fn complement_in(self, range: RangeInclusive<u8>) -> Self {
+        let range: CandidateSet = range.filter(|&key| key < 16).collect();
+        range.difference(self)
+    }
+
+    /// the smallest key.
+    pub fn min(self) -> Option<u8> {
+        if self.inner == 0 {
+            None
+        } else {
+            Some(self.inner.trailing_zeros() as u8)
         }
-        res
+    }
+
+    /// the largest key.
+    pub fn max(self) -> Option<u8> {
+        if self.inner == 0 {
+            None
+        } else {
+            Some(15 - self.inner.leading_zeros() as u8)
+        }
+    }
+
+    /// the key if set holds exactly one.
+    pub fn only(self) -> Option<u8> {
+        if self.count() == 1 {
+            self.min()
+        } else {
+            None
+        }
+    }
+
+    /// iterate the keys in set, from small to large.
+    pub fn iter(self) -> Candidates {
+        Candidates { bits: self.inner }
     }
 }
 
-impl BitOr for BitSet {
+impl fmt::Debug for CandidateSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<u8> for CandidateSet {
+    /// keys out of `0..16` are ignored.
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut set = CandidateSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<u8> for CandidateSet {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        for key in iter {
+            // out of bound keys are ignored
+            let _ = self.set(key);
+        }
+    }
+}
+
+impl IntoIterator for CandidateSet {
+    type Item = u8;
+    type IntoIter = Candidates;
+
+    fn into_iter(self) -> Candidates {
+        self.iter()
+    }
+}
+
+/// the values of a set, from small to large, without allocation.
+/// ```
+/// use sudoku_rs::board::Board;
+///
+/// let mut b = Board::new();
+/// b.set(0, 1, 3).unwrap();
+/// let mut candidates = b.candidates(0, 0);
+/// assert_eq!(candidates.len(), 8);
+/// assert_eq!(candidates.next(), Some(1));
+/// assert_eq!(candidates.next(), Some(2));
+/// assert_eq!(candidates.next(), Some(4));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Candidates {
+    bits: u16,
+}
+
+impl Iterator for Candidates {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.bits == 0 {
+            return None;
+        }
+        let key = self.bits.trailing_zeros() as u8;
+        // clear the lowest bit
+        self.bits &= self.bits - 1;
+        Some(key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Candidates {}
+
+impl BitOr for CandidateSet {
     type Output = Self;
 
     fn bitor(self, rhs: Self) -> Self {
-        Self {
-            inner: self.inner | rhs.inner,
-        }
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for CandidateSet {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+impl BitAnd for CandidateSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.intersection(rhs)
+    }
+}
+
+impl BitAndAssign for CandidateSet {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = self.intersection(rhs);
+    }
+}
+
+impl BitXor for CandidateSet {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        self.symmetric_difference(rhs)
+    }
+}
+
+impl Sub for CandidateSet {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.difference(rhs)
+    }
+}
+
+impl SubAssign for CandidateSet {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = self.difference(rhs);
+    }
+}
+
+impl Not for CandidateSet {
+    type Output = Self;
+
+    /// the complement in `1..=9`.
+    fn not(self) -> Self {
+        self.complement()
     }
 }
 
@@ -62,21 +358,21 @@ mod test {
     use super::*;
     #[test]
     fn set() {
-        let mut bitset = BitSet::new();
+        let mut bitset = CandidateSet::new();
         bitset.set(1).unwrap();
         assert_eq!(bitset.inner, 2);
     }
     #[test]
     fn set_get() {
-        let mut bitset = BitSet::new();
+        let mut bitset = CandidateSet::new();
         bitset.set(2).unwrap();
         assert!(bitset.get(2).unwrap());
     }
     #[test]
     fn and() {
-        let mut bitset_one = BitSet::new();
+        let mut bitset_one = CandidateSet::new();
         bitset_one.set(1).unwrap();
-        let mut bitset_another = BitSet::new();
+        let mut bitset_another = CandidateSet::new();
         bitset_another.set(0).unwrap();
         let bitset_and = bitset_one | bitset_another;
         assert!(bitset_and.get(0).unwrap());
@@ -84,7 +380,7 @@ mod test {
     }
     #[test]
     fn count() {
-        let mut bitset = BitSet::new();
+        let mut bitset = CandidateSet::new();
         bitset.set(0).unwrap();
         bitset.set(1).unwrap();
         bitset.set(2).unwrap();
@@ -93,19 +389,86 @@ mod test {
         assert_eq!(bitset.count(), 3);
     }
     #[test]
+    fn sum() {
+        let mut bitset = CandidateSet::new();
+        bitset.set(2).unwrap();
+        bitset.set(9).unwrap();
+        assert_eq!(bitset.sum(), 11);
+    }
+    #[test]
     fn remove() {
-        let mut bitset = BitSet::new();
+        let mut bitset = CandidateSet::new();
         bitset.set(1).unwrap();
         bitset.set(2).unwrap();
         bitset.remove(1).unwrap();
         assert_eq!(bitset.inner, 4);
     }
     #[test]
-    fn reverse() {
-        let mut bitset = BitSet::new();
-        bitset.set(1).unwrap();
-        bitset.set(2).unwrap();
-        let v = bitset.reverse(0..4);
-        assert_eq!(v, vec![0, 3]);
+    fn iter() {
+        let bitset = CandidateSet::from_bits(0b10_0100_1010);
+        assert_eq!(bitset.iter().collect::<Vec<_>>(), vec![1, 3, 6, 9]);
+        assert_eq!(bitset.iter().len(), 4);
+        assert_eq!(
+            bitset.complement().iter().collect::<Vec<_>>(),
+            vec![2, 4, 5, 7, 8]
+        );
+        assert_eq!(CandidateSet::new().iter().next(), None);
+    }
+    #[test]
+    fn algebra() {
+        let a: CandidateSet = vec![1, 2, 3, 5].into_iter().collect();
+        let b: CandidateSet = vec![3, 4, 5].into_iter().collect();
+        assert_eq!(a & b, CandidateSet::from_bits(0b10_1000));
+        assert_eq!(a | b, CandidateSet::from_bits(0b11_1110));
+        assert_eq!(a - b, CandidateSet::from_bits(0b110));
+        assert_eq!(a ^ b, CandidateSet::from_bits(0b1_0110));
+        assert_eq!(!a, a.complement());
+        assert!((a & b).is_subset(a));
+        assert!(!a.is_subset(b));
+        let mut c = a;
+        c -= b;
+        c |= CandidateSet::single(9);
+        c &= CandidateSet::full();
+        assert_eq!(format!("{:?}", c), "{1, 2, 9}");
+    }
+    #[test]
+    fn fast_path() {
+        let mut set = CandidateSet::new();
+        assert!(set.insert(4));
+        assert!(!set.insert(4));
+        assert!(set.contains(4));
+        assert!(set.discard(4));
+        assert!(!set.discard(4));
+        assert!(set.is_empty());
+        assert!(set.unchecked_insert(15));
+        assert!(set.unchecked_contains(15));
+        assert!(set.unchecked_discard(15));
+    }
+    #[test]
+    #[should_panic(expected = "bitset: key out of bound")]
+    fn insert_out_of_bound() {
+        CandidateSet::new().insert(16);
+    }
+    #[test]
+    #[should_panic(expected = "bitset: key out of bound")]
+    fn contains_out_of_bound() {
+        CandidateSet::full().contains(16);
+    }
+    #[test]
+    fn min_max_only() {
+        let set = CandidateSet::from_bits(0b10_0100_1000);
+        assert_eq!(set.min(), Some(3));
+        assert_eq!(set.max(), Some(9));
+        assert_eq!(set.only(), None);
+        assert_eq!(CandidateSet::single(7).only(), Some(7));
+        assert_eq!(CandidateSet::new().min(), None);
+        assert_eq!(CandidateSet::new().max(), None);
+        assert_eq!(
+            CandidateSet::full()
+                .complement_in(0..=20)
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![0, 10, 11, 12, 13, 14, 15]
+        );
     }
 }
@@ -6,19 +6,69 @@
 //! let sudoku_str = "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
 //! let board: Board = sudoku_str.parse().unwrap();
 //! ```
+//!
+//! the text format is the 81 slots, `1..=9` for value and any other char for empty.
+//! whitespace is ignored, so the output of `Display` can be parsed back.
+//! variants are given by `key: value` lines after the slots.
+//! ```
+//! use sudoku_rs::board::Board;
+//!
+//! let sudoku_str = "000000000000000000000000000000000000000010000000000000000000000000000000000000000
+//! variant: diagonal";
+//! let board: Board = sudoku_str.parse().unwrap();
+//! assert!(board.is_diagonal());
+//! assert!(!board.avaliable_val(0, 0).contains(&1));
+//! ```
+
+use crate::{
+    bitset::CandidateSet,
+    cage::{self, Cage},
+    constraint::{self, Constraint},
+    error::*,
+    prelude::*,
+};
+#[cfg(not(target_has_atomic = "ptr"))]
+use alloc::rc::Rc as Arc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use core::{fmt, num::NonZeroU8};
 
-use crate::{bitset::BitSet, error::*};
-use std::{fmt, num::NonZeroU8};
+pub use crate::bitset::Candidates;
 
 type Grid = Option<NonZeroU8>;
 
+/// region id of every slot, `x * 9 + y` for index.
+pub type Regions = [u8; 81];
+
+/// the 3x3 boxes of classic sudoku.
+pub const BOX_REGIONS: Regions = {
+    let mut regions = [0; 81];
+    let mut pos = 0;
+    while pos < 81 {
+        let (x, y) = (pos / 9, pos % 9);
+        regions[pos] = ((x / 3 * 3) + (y / 3)) as u8;
+        pos += 1;
+    }
+    regions
+};
+
 /// board struct
 #[derive(Clone)]
 pub struct Board {
     inner: [Grid; 81],
-    mat: [BitSet; 9],
-    x: [BitSet; 9],
-    y: [BitSet; 9],
+    mat: [CandidateSet; 9],
+    x: [CandidateSet; 9],
+    y: [CandidateSet; 9],
+    /// region id of every slot, `mat` is indexed by it.
+    regions: Regions,
+    /// main diagonal and anti diagonal, only for sudoku-x.
+    diag: Option<[CandidateSet; 2]>,
+    /// killer cages, `cage_used` hold the values placed in each.
+    cages: Vec<Cage>,
+    cage_used: Vec<CandidateSet>,
+    cage_of: [Option<u8>; 81],
+    /// other rules of variants.
+    constraints: Vec<Arc<dyn Constraint>>,
 }
 
 impl Default for Board {
@@ -31,10 +81,206 @@ impl Board {
     pub fn new() -> Self {
         Board {
             inner: [None; 81],
-            x: [BitSet::new(); 9],
-            y: [BitSet::new(); 9],
-            mat: [BitSet::new(); 9],
+            x: [CandidateSet::new(); 9],
+            y: [CandidateSet::new(); 9],
+            mat: [CandidateSet::new(); 9],
+            regions: BOX_REGIONS,
+            diag: None,
+            cages: Vec::new(),
+            cage_used: Vec::new(),
+            cage_of: [None; 81],
+            constraints: Vec::new(),
+        }
+    }
+
+    /// return a new jigsaw board.
+    /// every region must have exactly nine connected slots.
+    /// ```
+    /// use sudoku_rs::board::Board;
+    ///
+    /// let mut regions = [0; 81];
+    /// for (pos, region) in regions.iter_mut().enumerate() {
+    ///     // nine rows as regions
+    ///     *region = (pos / 9) as u8;
+    /// }
+    /// let b = Board::with_regions(regions).unwrap();
+    /// assert_eq!(b.region(4, 8), 4);
+    ///
+    /// regions[0] = 1;
+    /// assert!(Board::with_regions(regions).is_err());
+    /// ```
+    pub fn with_regions(regions: Regions) -> Result<Self, SuDoKuError> {
+        let mut board = Board::new();
+        board.set_regions(regions)?;
+        Ok(board)
+    }
+
+    /// the region id of a slot.
+    pub fn region(&self, x: usize, y: usize) -> u8 {
+        self.regions[x * 9 + y]
+    }
+
+    /// the region id of every slot.
+    pub fn regions(&self) -> &Regions {
+        &self.regions
+    }
+
+    /// check if the regions are not the classic boxes.
+    pub fn is_jigsaw(&self) -> bool {
+        self.regions != BOX_REGIONS
+    }
+
+    /// change the region layout, values on board are kept.
+    pub fn set_regions(&mut self, regions: Regions) -> Result<(), SuDoKuError> {
+        check_regions(&regions)?;
+        self.regions = regions;
+        self.mat = [CandidateSet::new(); 9];
+        for (pos, grid) in self.inner.iter().enumerate() {
+            if let Some(val) = grid {
+                self.mat[regions[pos] as usize]
+                    .set(val.get())
+                    .expect("mat: out of bound");
+            }
+        }
+        Ok(())
+    }
+
+    /// return a new sudoku-x board.
+    /// both main diagonals must also hold 1..=9.
+    /// ```
+    /// use sudoku_rs::board::Board;
+    ///
+    /// let mut b = Board::new_diagonal();
+    /// b.set(0, 0, 1).unwrap();
+    /// assert!(!b.avaliable_val(8, 8).contains(&1));
+    /// assert!(b.avaliable_val(8, 1).contains(&1));
+    /// ```
+    pub fn new_diagonal() -> Self {
+        let mut board = Board::new();
+        board.set_diagonal(true);
+        board
+    }
+
+    /// check if both main diagonals must hold 1..=9.
+    pub fn is_diagonal(&self) -> bool {
+        self.diag.is_some()
+    }
+
+    /// turn the diagonal constraint on or off.
+    pub fn set_diagonal(&mut self, diagonal: bool) {
+        if !diagonal {
+            self.diag = None;
+            return;
+        }
+        let mut diag = [CandidateSet::new(); 2];
+        for i in 0..9 {
+            if let Some(val) = self.unchecked_get(i, i) {
+                diag[0].set(val).expect("diag: out of bound");
+            }
+            if let Some(val) = self.unchecked_get(i, 8 - i) {
+                diag[1].set(val).expect("diag: out of bound");
+            }
+        }
+        self.diag = Some(diag);
+    }
+
+    /// the values used by the diagonals which contain a slot.
+    fn diag_used(&self, x: usize, y: usize) -> CandidateSet {
+        let mut used = CandidateSet::new();
+        if let Some(diag) = &self.diag {
+            if x == y {
+                used |= diag[0];
+            }
+            if x + y == 8 {
+                used |= diag[1];
+            }
+        }
+        used
+    }
+
+    /// add a killer cage.
+    /// a slot can be in only one cage.
+    pub fn add_cage(&mut self, cage: Cage) -> Result<(), SuDoKuError> {
+        if self.cages.len() >= 81
+            || cage
+                .slots()
+                .iter()
+                .any(|&(x, y)| self.cage_of[x * 9 + y].is_some())
+        {
+            return Err(SuDoKuError::InvalidCage);
+        }
+        let id = self.cages.len() as u8;
+        let mut used = CandidateSet::new();
+        for &(x, y) in cage.slots() {
+            self.cage_of[x * 9 + y] = Some(id);
+            if let Some(val) = self.unchecked_get(x, y) {
+                used.set(val).expect("cage: out of bound");
+            }
+        }
+        self.cages.push(cage);
+        self.cage_used.push(used);
+        Ok(())
+    }
+
+    /// the killer cages.
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// check if the board has killer cages.
+    pub fn is_killer(&self) -> bool {
+        !self.cages.is_empty()
+    }
+
+    /// add a constraint of variant.
+    pub fn add_constraint<C: Constraint + 'static>(&mut self, constraint: C) {
+        self.constraints.push(Arc::new(constraint));
+    }
+
+    /// the constraints of variants.
+    /// they are shared by `Rc` on targets without pointer sized atomics.
+    pub fn constraints(&self) -> &[Arc<dyn Constraint>] {
+        &self.constraints
+    }
+
+    /// check if only the classic rules are on the board.
+    pub fn is_classic(&self) -> bool {
+        !self.is_diagonal() && !self.is_jigsaw() && !self.is_killer() && self.constraints.is_empty()
+    }
+
+    /// the values allowed by the cage which contain a slot.
+    /// the value of the slot itself is treated as not placed.
+    fn cage_allowed(&self, pos: usize) -> CandidateSet {
+        let id = match self.cage_of[pos] {
+            Some(id) => id as usize,
+            None => return CandidateSet::from_bits(0b11_1111_1110),
+        };
+        let mut used = self.cage_used[id];
+        if let Some(this) = self.inner[pos] {
+            used.remove(this.get()).expect("range out");
+        }
+        let placed = used.sum();
+        let cage = &self.cages[id];
+        match cage.sum().checked_sub(placed) {
+            Some(left) => cage::combinations(left, cage.slots().len() - used.count(), used),
+            None => CandidateSet::new(),
+        }
+    }
+
+    /// check the cage which contain a slot can still be completed.
+    pub(crate) fn cage_feasible(&self, x: usize, y: usize) -> bool {
+        let id = match self.cage_of[x * 9 + y] {
+            Some(id) => id as usize,
+            None => return true,
+        };
+        let cage = &self.cages[id];
+        let used = self.cage_used[id];
+        if used.count() == cage.slots().len() {
+            return used.sum() == cage.sum();
         }
+        cage.slots()
+            .iter()
+            .all(|&(cx, cy)| !self.is_empty(cx, cy) || self.avaliable_count(cx, cy) > 0)
     }
 
     /// gen board from vec.
@@ -76,16 +322,50 @@ impl Board {
     /// will also set bitset.
     pub fn unchecked_set(&mut self, x: usize, y: usize, val: u8) {
         let pos = x * 9 + y;
-        let mat_id = (x / 3 * 3) + (y / 3);
+        let mat_id = self.regions[pos] as usize;
         if let Some(before) = self.inner[pos].map(|nz| nz.get()) {
             self.x[x].remove(before).expect("x: should be a value");
             self.y[y].remove(before).expect("y: should be a value");
             self.mat[mat_id].remove(before).expect("should be a value");
+            self.diag_remove(x, y, before);
+            self.cage_remove(pos, before);
         }
         self.inner[pos] = NonZeroU8::new(val);
         self.x[x].set(val).expect("x: out of bound");
         self.y[y].set(val).expect("y: out of bound");
         self.mat[mat_id].set(val).expect("mat: out of bound");
+        if let Some(diag) = &mut self.diag {
+            if x == y {
+                diag[0].set(val).expect("diag: out of bound");
+            }
+            if x + y == 8 {
+                diag[1].set(val).expect("diag: out of bound");
+            }
+        }
+        if let Some(id) = self.cage_of[pos] {
+            self.cage_used[id as usize]
+                .set(val)
+                .expect("cage: out of bound");
+        }
+    }
+
+    fn cage_remove(&mut self, pos: usize, val: u8) {
+        if let Some(id) = self.cage_of[pos] {
+            self.cage_used[id as usize]
+                .remove(val)
+                .expect("cage: should be a value");
+        }
+    }
+
+    fn diag_remove(&mut self, x: usize, y: usize, val: u8) {
+        if let Some(diag) = &mut self.diag {
+            if x == y {
+                diag[0].remove(val).expect("diag: should be a value");
+            }
+            if x + y == 8 {
+                diag[1].remove(val).expect("diag: should be a value");
+            }
+        }
     }
 
     /// get value.
@@ -96,8 +376,9 @@ impl Board {
     }
 
     /// set value.
+    #[allow(clippy::manual_range_contains)]
     pub fn set(&mut self, x: usize, y: usize, val: u8) -> Result<(), SuDoKuError> {
-        if x >= 9 && y >= 9 {
+        if x >= 9 || y >= 9 {
             return Err(SuDoKuError::OutOfBound);
         }
         if val > 9 || val < 1 {
@@ -109,7 +390,7 @@ impl Board {
 
     /// get value.
     pub fn get(&self, x: usize, y: usize) -> Result<Option<u8>, SuDoKuError> {
-        if x >= 9 && y >= 9 {
+        if x >= 9 || y >= 9 {
             return Err(SuDoKuError::OutOfBound);
         }
         Ok(self.unchecked_get(x, y))
@@ -128,56 +409,370 @@ impl Board {
         self.get(x, y).unwrap_or(Some(0)).is_none()
     }
 
-    /// clear a slot
+    /// clear a slot, a slot out of bound is ignored.
     pub fn unset(&mut self, x: usize, y: usize) {
+        if x >= 9 || y >= 9 {
+            return;
+        }
         let pos = x * 9 + y;
-        let mat_id = (x / 3 * 3) + (y / 3);
+        let mat_id = self.regions[pos] as usize;
         if let Some(before) = self.inner[pos].map(|nz| nz.get()) {
             self.x[x].remove(before).expect("x: should be a value");
             self.y[y].remove(before).expect("y: should be a value");
             self.mat[mat_id]
                 .remove(before)
                 .expect("mat: should be a value");
+            self.diag_remove(x, y, before);
+            self.cage_remove(pos, before);
             self.inner[pos] = None;
         }
     }
 
-    /// get avaliable values for a slot.
+    /// get avaliable values for a slot, none for a slot out of bound.
     pub fn avaliable_val(&self, x: usize, y: usize) -> Vec<u8> {
-        let pos = x * 9 + y;
-        let mat_id = (x / 3 * 3) + (y / 3);
-        let mut cross = self.x[x] | self.y[y] | self.mat[mat_id];
-        if let Some(this) = self.inner[pos] {
-            cross.remove(this.get()).expect("range out");
+        if x >= 9 || y >= 9 {
+            return Vec::new();
         }
-        cross.reverse(1..10)
+        self.candidates(x, y).collect()
+    }
+
+    /// iterate avaliable values for a slot, without allocation.
+    pub fn candidates(&self, x: usize, y: usize) -> Candidates {
+        self.candidate_set(x, y).iter()
+    }
+
+    /// get avaliable values for a slot as a set.
+    /// ```
+    /// use sudoku_rs::board::Board;
+    ///
+    /// let mut b = Board::new();
+    /// b.set(0, 1, 3).unwrap();
+    /// b.set(1, 0, 4).unwrap();
+    /// let set = b.candidate_set(0, 0) & b.candidate_set(8, 8);
+    /// assert_eq!(set.count(), 7);
+    /// assert_eq!(set.min(), Some(1));
+    /// ```
+    pub fn candidate_set(&self, x: usize, y: usize) -> CandidateSet {
+        self.blocked(x, y).complement()
     }
 
     /// get avaliable values count for a slot.
     pub fn avaliable_count(&self, x: usize, y: usize) -> usize {
+        9 - self.blocked(x, y).count()
+    }
+
+    /// the values which can not be put on a slot, only 1..=9 are used.
+    pub(crate) fn blocked(&self, x: usize, y: usize) -> CandidateSet {
         let pos = x * 9 + y;
-        let mat_id = (x / 3 * 3) + (y / 3);
-        let mut cross = self.x[x] | self.y[y] | self.mat[mat_id];
+        let mat_id = self.regions[pos] as usize;
+        let mut cross = self.x[x] | self.y[y] | self.mat[mat_id] | self.diag_used(x, y);
         if let Some(this) = self.inner[pos] {
             cross.remove(this.get()).expect("range out");
         }
-        9 - cross.count()
+        if self.cage_of[pos].is_some() {
+            cross |= self.cage_allowed(pos).complement();
+        }
+        for constraint in self.constraints.iter() {
+            for val in cross.complement() {
+                if !constraint.allows(self, x, y, val) {
+                    cross.unchecked_insert(val);
+                }
+            }
+        }
+        cross
     }
 
-    /// check if a board is filled.
+    /// check if a board is filled and satisfies its cages and constraints.
     pub fn is_win(&self) -> bool {
         self.x.iter().filter(|bs| bs.count() == 9).count() == 9
             && self.y.iter().filter(|bs| bs.count() == 9).count() == 9
             && self.mat.iter().filter(|bs| bs.count() == 9).count() == 9
+            && self.diag.iter().flatten().all(|bs| bs.count() == 9)
+            && self
+                .cages
+                .iter()
+                .zip(self.cage_used.iter())
+                .all(|(cage, used)| used.count() == cage.slots().len() && used.sum() == cage.sum())
+            && self.constraints.iter().all(|c| c.check(self))
     }
 }
 
-impl std::str::FromStr for Board {
+/// check every region has exactly nine connected slots.
+fn check_regions(regions: &Regions) -> Result<(), SuDoKuError> {
+    let mut sizes = [0; 9];
+    for &region in regions.iter() {
+        if region >= 9 {
+            return Err(SuDoKuError::InvalidRegion);
+        }
+        sizes[region as usize] += 1;
+    }
+    if sizes.iter().any(|&size| size != 9) {
+        return Err(SuDoKuError::InvalidRegion);
+    }
+    let mut seen = [false; 81];
+    for start in 0..81 {
+        if seen[start] {
+            continue;
+        }
+        // flood fill from the first slot of a region
+        let region = regions[start];
+        let mut stack = vec![start];
+        let mut size = 0;
+        seen[start] = true;
+        while let Some(pos) = stack.pop() {
+            size += 1;
+            let (x, y) = (pos / 9, pos % 9);
+            let mut next = Vec::with_capacity(4);
+            if x > 0 {
+                next.push(pos - 9);
+            }
+            if x < 8 {
+                next.push(pos + 9);
+            }
+            if y > 0 {
+                next.push(pos - 1);
+            }
+            if y < 8 {
+                next.push(pos + 1);
+            }
+            for n in next {
+                if !seen[n] && regions[n] == region {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        if size != 9 {
+            return Err(SuDoKuError::InvalidRegion);
+        }
+    }
+    Ok(())
+}
+
+impl Board {
+    /// apply a `key: value` line of the text format.
+    fn apply_directive(&mut self, key: &str, value: &str) -> Result<(), SuDoKuError> {
+        match (key, value) {
+            ("variant", "diagonal") => self.set_diagonal(true),
+            ("regions", value) => {
+                let mut regions = [0; 81];
+                let mut chs = value.chars().filter(|ch| !ch.is_whitespace());
+                for region in regions.iter_mut() {
+                    // regions are written as 1..=9
+                    *region = match chs.next().and_then(|ch| ch.to_digit(10)) {
+                        Some(n @ 1..=9) => n as u8 - 1,
+                        _ => return Err(SuDoKuError::InvalidFormat),
+                    };
+                }
+                if chs.next().is_some() {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+                self.set_regions(regions)?;
+            }
+            ("cage", value) => self.add_cage(value.parse()?)?,
+            (key, value) => match constraint::parse_directive(key, value) {
+                Some(constraint) => self.constraints.push(constraint?),
+                None => return Err(SuDoKuError::InvalidFormat),
+            },
+        }
+        Ok(())
+    }
+}
+
+/// the version of the bytes format, in the high half of the first byte.
+const BYTES_VERSION: u8 = 1;
+/// 4 bits per slot, the even slot in the low half.
+const BYTES_SLOTS: u8 = 0;
+/// a bit per slot for the givens, then 4 bits per given.
+const BYTES_GIVENS: u8 = 1;
+/// a full grid as the index of every value in the values left for its slot,
+/// read as one mixed radix number of 16 bytes.
+const BYTES_GRID: u8 = 2;
+
+/// the values of `cells` packed 4 bits each.
+fn pack_nibbles(cells: impl Iterator<Item = u8>, out: &mut Vec<u8>) {
+    for (i, val) in cells.enumerate() {
+        if i % 2 == 0 {
+            out.push(val);
+        } else {
+            *out.last_mut().expect("pushed above") |= val << 4;
+        }
+    }
+}
+
+fn nibble(bytes: &[u8], i: usize) -> u8 {
+    bytes[i / 2] >> (i % 2 * 4) & 0xf
+}
+
+/// the values left for a slot of a grid filled row by row up to it.
+fn grid_left(rows: &[u16; 9], cols: &[u16; 9], boxes: &[u16; 9], pos: usize) -> u16 {
+    let (x, y) = (pos / 9, pos % 9);
+    !(rows[x] | cols[y] | boxes[x / 3 * 3 + y / 3]) & 0x3fe
+}
+
+impl Board {
+    /// encode the values of a classic board in a compact form.
+    /// the first byte is the version and the kind of encoding, the smallest kind is picked:
+    /// 17 bytes for a solved grid, 12 bytes and 4 bits per given for a puzzle
+    /// and 42 bytes at most.
+    /// fail with `SuDoKuError::InvalidFormat` for a variant, which has only the text format.
+    /// ```
+    /// use sudoku_rs::board::Board;
+    ///
+    /// let sudoku_str = "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+    /// let board: Board = sudoku_str.parse().unwrap();
+    /// let bytes = board.to_bytes().unwrap();
+    /// assert_eq!(bytes.len(), 21);
+    /// assert_eq!(Board::from_bytes(&bytes).unwrap().to_string(), board.to_string());
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SuDoKuError> {
+        if !self.is_classic() {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        let cells: Vec<u8> = (0..81)
+            .map(|pos| self.unchecked_get(pos / 9, pos % 9).unwrap_or(0))
+            .collect();
+        if let Some(code) = Self::grid_code(&cells) {
+            let mut bytes = vec![BYTES_VERSION << 4 | BYTES_GRID];
+            bytes.extend_from_slice(&code.to_le_bytes());
+            return Ok(bytes);
+        }
+        let givens = cells.iter().filter(|&&val| val != 0).count();
+        let mut bytes = Vec::with_capacity(42);
+        if 11 + (givens + 1) / 2 < 41 {
+            bytes.push(BYTES_VERSION << 4 | BYTES_GIVENS);
+            let mut mask = [0; 11];
+            for (pos, _) in cells.iter().enumerate().filter(|(_, &val)| val != 0) {
+                mask[pos / 8] |= 1 << (pos % 8);
+            }
+            bytes.extend_from_slice(&mask);
+            pack_nibbles(cells.into_iter().filter(|&val| val != 0), &mut bytes);
+        } else {
+            bytes.push(BYTES_VERSION << 4 | BYTES_SLOTS);
+            pack_nibbles(cells.into_iter(), &mut bytes);
+        }
+        Ok(bytes)
+    }
+
+    /// the code of a valid full grid, none for anything else.
+    fn grid_code(cells: &[u8]) -> Option<u128> {
+        let (mut rows, mut cols, mut boxes) = ([0u16; 9], [0u16; 9], [0u16; 9]);
+        let (mut code, mut scale) = (0u128, 1u128);
+        for (pos, &val) in cells.iter().enumerate() {
+            let left = grid_left(&rows, &cols, &boxes, pos);
+            if val == 0 || left & (1 << val) == 0 {
+                return None;
+            }
+            let index = (left & ((1 << val) - 1)).count_ones();
+            code += u128::from(index) * scale;
+            // at most 9^1 * 8^3 * 7^5 * .. * 2^15 in total, below 2^123
+            scale *= u128::from(left.count_ones());
+            let (x, y) = (pos / 9, pos % 9);
+            rows[x] |= 1 << val;
+            cols[y] |= 1 << val;
+            boxes[x / 3 * 3 + y / 3] |= 1 << val;
+        }
+        Some(code)
+    }
+
+    /// decode a board of `to_bytes`.
+    /// fail with `SuDoKuError::InvalidFormat` for an unknown version or kind, a wrong length,
+    /// a value out of range, a mask bit past the last slot, padding which is not zero,
+    /// or a grid code which does not end with the last slot.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SuDoKuError> {
+        let (&head, body) = bytes.split_first().ok_or(SuDoKuError::InvalidFormat)?;
+        if head >> 4 != BYTES_VERSION {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        let mut cells = [0; 81];
+        match head & 0xf {
+            BYTES_SLOTS if body.len() == 41 => {
+                // the high nibble of the last byte is padding
+                if body[40] >> 4 != 0 {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+                for (pos, cell) in cells.iter_mut().enumerate() {
+                    *cell = nibble(body, pos);
+                    if *cell > 9 {
+                        return Err(SuDoKuError::InvalidFormat);
+                    }
+                }
+            }
+            BYTES_GIVENS if body.len() >= 11 => {
+                let (mask, values) = body.split_at(11);
+                // only the lowest bit of the last mask byte is a slot
+                if mask[10] >> 1 != 0 {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+                let givens: Vec<usize> = (0..81)
+                    .filter(|&pos| mask[pos / 8] & (1 << (pos % 8)) != 0)
+                    .collect();
+                if values.len() != (givens.len() + 1) / 2 {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+                if givens.len() % 2 == 1 && values[values.len() - 1] >> 4 != 0 {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+                for (i, &pos) in givens.iter().enumerate() {
+                    cells[pos] = nibble(values, i);
+                    if !(1..=9).contains(&cells[pos]) {
+                        return Err(SuDoKuError::InvalidFormat);
+                    }
+                }
+            }
+            BYTES_GRID if body.len() == 16 => {
+                let mut code = [0; 16];
+                code.copy_from_slice(body);
+                let mut code = u128::from_le_bytes(code);
+                let (mut rows, mut cols, mut boxes) = ([0u16; 9], [0u16; 9], [0u16; 9]);
+                for (pos, cell) in cells.iter_mut().enumerate() {
+                    let left = grid_left(&rows, &cols, &boxes, pos);
+                    let count = u128::from(left.count_ones());
+                    // a code out of range runs into a slot without values
+                    if count == 0 {
+                        return Err(SuDoKuError::InvalidFormat);
+                    }
+                    let index = (code % count) as usize;
+                    code /= count;
+                    let val = CandidateSet::from_bits(left)
+                        .iter()
+                        .nth(index)
+                        .expect("index below count");
+                    *cell = val;
+                    let (x, y) = (pos / 9, pos % 9);
+                    rows[x] |= 1 << val;
+                    cols[y] |= 1 << val;
+                    boxes[x / 3 * 3 + y / 3] |= 1 << val;
+                }
+                if code != 0 {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+            }
+            _ => return Err(SuDoKuError::InvalidFormat),
+        }
+        let mut board = Board::new();
+        for (pos, &val) in cells.iter().enumerate() {
+            if val != 0 {
+                board.set(pos / 9, pos % 9, val)?;
+            }
+        }
+        Ok(board)
+    }
+}
+
+impl core::str::FromStr for Board {
     type Err = SuDoKuError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut board = Board::new();
-        let mut chs = s.chars();
+        let mut slots = String::new();
+        for line in s.lines() {
+            match line.split_once(':') {
+                Some((key, value)) => board.apply_directive(key.trim(), value.trim())?,
+                None => slots.extend(line.chars().filter(|ch| !ch.is_whitespace())),
+            }
+        }
+        let mut chs = slots.chars();
         for x in 0..9 {
             for y in 0..9 {
                 let ch = chs.next().unwrap_or('.');
@@ -206,6 +801,21 @@ impl fmt::Display for Board {
             }
             matrix.push('\n');
         }
+        if self.is_diagonal() {
+            matrix.push_str("variant: diagonal\n");
+        }
+        if self.is_jigsaw() {
+            matrix.push_str("regions: ");
+            matrix.extend(self.regions.iter().map(|&region| char::from(region + b'1')));
+            matrix.push('\n');
+        }
+        for cage in self.cages.iter() {
+            matrix.push_str(&format!("cage: {}\n", cage));
+        }
+        for directive in self.constraints.iter().filter_map(|c| c.directive()) {
+            matrix.push_str(&directive);
+            matrix.push('\n');
+        }
         write!(f, "{}", matrix)
     }
 }
@@ -213,6 +823,10 @@ impl fmt::Display for Board {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        generator::Generator,
+        rng::{Rng, SplitMix64},
+    };
 
     #[test]
     fn set_edge_0() {
@@ -226,6 +840,19 @@ mod test {
         board.set(8, 8, 9).unwrap();
     }
 
+    #[test]
+    fn out_of_bound() {
+        let mut board = Board::new();
+        assert!(matches!(board.set(0, 9, 1), Err(SuDoKuError::OutOfBound)));
+        assert!(matches!(board.set(9, 0, 1), Err(SuDoKuError::OutOfBound)));
+        assert!(matches!(board.get(0, 9), Err(SuDoKuError::OutOfBound)));
+        assert!(matches!(board.get(9, 0), Err(SuDoKuError::OutOfBound)));
+        board.unset(9, 0);
+        board.unset(0, 9);
+        assert!(board.avaliable_val(9, 0).is_empty());
+        assert!(board.avaliable_val(0, 9).is_empty());
+    }
+
     #[test]
     fn win_check() {
         let mut board = Board::from_vec(vec![
@@ -271,6 +898,259 @@ mod test {
         assert_eq!("_ _ _ _ _ _ _ _ _\n_ _ 3 _ _ _ _ _ _\n_ _ _ _ _ _ _ _ _\n_ _ _ _ _ _ _ _ _\n_ _ _ _ _ _ _ _ _\n_ _ _ _ _ _ _ _ _\n_ _ _ _ _ _ _ _ _\n_ _ _ _ _ _ _ _ _\n_ _ _ _ _ _ _ _ _\n".to_string(), format!("{}", board));
     }
 
+    #[test]
+    fn diagonal() {
+        let mut board = Board::new_diagonal();
+        board.set(2, 6, 5).unwrap();
+        assert!(!board.avaliable_val(6, 2).contains(&5));
+        assert!(board.avaliable_val(5, 5).contains(&5));
+        assert_eq!(board.avaliable_count(4, 4), 8);
+        board.unset(2, 6);
+        assert!(board.avaliable_val(6, 2).contains(&5));
+    }
+
+    #[test]
+    fn diagonal_win_check() {
+        let board = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        let mut diagonal = board.clone();
+        diagonal.set_diagonal(true);
+        assert!(board.is_win());
+        assert!(!diagonal.is_win());
+    }
+
+    #[test]
+    fn diagonal_round_trip() {
+        let mut board = Board::new_diagonal();
+        board.set(4, 4, 7).unwrap();
+        let parsed: Board = board.to_string().parse().unwrap();
+        assert!(parsed.is_diagonal());
+        assert_eq!(parsed.get(4, 4).unwrap(), Some(7));
+        assert_eq!(parsed.to_string(), board.to_string());
+    }
+
+    /// classic boxes, but (0, 3) moved to the first box and (2, 2) to the second.
+    const JIGSAW: &str =
+        "111122333111222333112222333444555666444555666444555666777888999777888999777888999";
+
+    fn jigsaw_regions() -> Regions {
+        let mut regions = [0; 81];
+        for (region, ch) in regions.iter_mut().zip(JIGSAW.bytes()) {
+            *region = ch - b'1';
+        }
+        regions
+    }
+
+    #[test]
+    fn jigsaw() {
+        let mut board = Board::with_regions(jigsaw_regions()).unwrap();
+        assert!(board.is_jigsaw());
+        assert_eq!(board.region(0, 8), 2);
+        assert_eq!(board.region(2, 0), 0);
+        // (0, 3) and (2, 0) share region 0 but neither row nor column
+        board.set(0, 3, 4).unwrap();
+        assert!(!board.avaliable_val(2, 0).contains(&4));
+        assert!(board.avaliable_val(2, 2).contains(&4));
+        board.unset(0, 3);
+        assert!(board.avaliable_val(2, 0).contains(&4));
+    }
+
+    #[test]
+    fn jigsaw_invalid() {
+        let mut regions = jigsaw_regions();
+        regions.swap(0, 80);
+        assert!(Board::with_regions(regions).is_err());
+        // right size but not connected
+        let mut regions = BOX_REGIONS;
+        regions.swap(0, 10);
+        regions.swap(1, 30);
+        assert!(Board::with_regions(regions).is_err());
+    }
+
+    #[test]
+    fn jigsaw_round_trip() {
+        let mut board = Board::with_regions(jigsaw_regions()).unwrap();
+        board.set(0, 0, 1).unwrap();
+        let text = board.to_string();
+        assert!(text.ends_with(&format!("regions: {}\n", JIGSAW)));
+        let parsed: Board = text.parse().unwrap();
+        assert_eq!(parsed.regions(), board.regions());
+        assert_eq!(parsed.get(0, 0).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn killer() {
+        let mut board = Board::new();
+        board
+            .add_cage(Cage::new(10, vec![(0, 0), (0, 1), (1, 0)]).unwrap())
+            .unwrap();
+        assert!(board.is_killer());
+        assert_eq!(board.avaliable_count(0, 0), 7);
+        board.set(0, 0, 5).unwrap();
+        // 5 left for two slots, 1 + 4 or 2 + 3
+        assert_eq!(board.avaliable_val(0, 1), vec![1, 2, 3, 4]);
+        board.set(0, 1, 1).unwrap();
+        assert_eq!(board.avaliable_val(1, 0), vec![4]);
+        // the value of the slot itself does not count
+        assert_eq!(board.avaliable_val(0, 0), vec![2, 3, 4, 5, 6, 7]);
+        board.unset(0, 0);
+        assert_eq!(board.avaliable_val(1, 0), vec![2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn killer_overlap() {
+        let mut board = Board::new();
+        board
+            .add_cage(Cage::new(10, vec![(0, 0), (0, 1)]).unwrap())
+            .unwrap();
+        assert!(board
+            .add_cage(Cage::new(10, vec![(0, 1), (0, 2)]).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn killer_win_check() {
+        let mut board = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        let mut wrong = board.clone();
+        board
+            .add_cage(Cage::new(14, vec![(0, 0), (0, 1)]).unwrap())
+            .unwrap();
+        wrong
+            .add_cage(Cage::new(13, vec![(0, 0), (0, 1)]).unwrap())
+            .unwrap();
+        assert!(board.is_win());
+        assert!(!wrong.is_win());
+    }
+
+    #[test]
+    fn killer_round_trip() {
+        let mut board = Board::new();
+        board
+            .add_cage(Cage::new(3, vec![(0, 0), (0, 1)]).unwrap())
+            .unwrap();
+        board
+            .add_cage(Cage::new(24, vec![(8, 8), (8, 7), (7, 8)]).unwrap())
+            .unwrap();
+        let text = board.to_string();
+        assert!(text.ends_with("cage: 3 r1c1 r1c2\ncage: 24 r9c9 r9c8 r8c9\n"));
+        let parsed: Board = text.parse().unwrap();
+        assert_eq!(parsed.cages(), board.cages());
+        assert_eq!(parsed.avaliable_val(7, 8), vec![7, 8, 9]);
+    }
+
+    #[test]
+    fn from_str_unknown_directive() {
+        assert!("variant: nothing".parse::<Board>().is_err());
+    }
+
+    /// every slot of the text and of the bytes, 0 for empty.
+    fn cells(board: &Board) -> Vec<u8> {
+        (0..81)
+            .map(|pos| board.unchecked_get(pos / 9, pos % 9).unwrap_or(0))
+            .collect()
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut generator = Generator::new(5);
+        let mut rng = SplitMix64::new(5);
+        for _ in 0..20 {
+            let grid = generator.solution(&Board::new()).unwrap();
+            // a puzzle, a partly filled grid and a random board which is not a sudoku
+            let puzzle = generator.generate(&Board::new()).unwrap();
+            let mut partial = grid.clone();
+            for _ in 0..rng.below(81) {
+                let pos = rng.below(81);
+                partial.unset(pos / 9, pos % 9);
+            }
+            let noise: String = (0..81)
+                .map(|_| char::from(b'0' + rng.below(10) as u8))
+                .collect();
+            for board in [grid, puzzle, partial].iter() {
+                let text: Board = board.to_string().parse().unwrap();
+                let bytes = text.to_bytes().unwrap();
+                assert!(bytes.len() <= 42);
+                assert_eq!(cells(&Board::from_bytes(&bytes).unwrap()), cells(&text));
+            }
+            let noise: Board = noise.parse().unwrap();
+            let bytes = noise.to_bytes().unwrap();
+            assert_eq!(cells(&Board::from_bytes(&bytes).unwrap()), cells(&noise));
+        }
+    }
+
+    #[test]
+    fn bytes_kinds() {
+        let mut grid = Generator::new(1).solution(&Board::new()).unwrap();
+        let bytes = grid.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 17);
+        assert_eq!(bytes[0], 0x12);
+        assert_eq!(Board::new().to_bytes().unwrap(), {
+            let mut empty = vec![0x11];
+            empty.extend_from_slice(&[0; 11]);
+            empty
+        });
+        grid.unset(8, 8);
+        assert_eq!(grid.to_bytes().unwrap().len(), 42);
+        assert!(Board::new_diagonal().to_bytes().is_err());
+        assert!(Board::from_bytes(&[]).is_err());
+        assert!(Board::from_bytes(&[0x21; 42]).is_err());
+        assert!(Board::from_bytes(&bytes[..16]).is_err());
+        let mut code = bytes.clone();
+        code[16] = 0xff;
+        assert!(Board::from_bytes(&code).is_err());
+    }
+
+    #[test]
+    fn bytes_invalid() {
+        let invalid =
+            |bytes: &[u8]| matches!(Board::from_bytes(bytes), Err(SuDoKuError::InvalidFormat));
+        let mut board = Board::new();
+        board.set(0, 0, 5).unwrap();
+        let givens = board.to_bytes().unwrap();
+        assert_eq!(givens, [&[0x11, 1][..], &[0; 10], &[5]].concat());
+        // a value out of 1..=9
+        for val in [0, 10, 15].iter() {
+            let mut bytes = givens.clone();
+            bytes[12] = *val;
+            assert!(invalid(&bytes));
+        }
+        // padding after an odd count of values
+        let mut bytes = givens.clone();
+        bytes[12] |= 0x10;
+        assert!(invalid(&bytes));
+        // mask bits past slot 80
+        for bit in 1..8 {
+            let mut bytes = givens.clone();
+            bytes[11] |= 1 << bit;
+            assert!(invalid(&bytes));
+        }
+
+        let mut slots = vec![0x10];
+        slots.extend_from_slice(&[0; 41]);
+        assert!(Board::from_bytes(&slots).is_ok());
+        for val in [10, 15].iter() {
+            let mut bytes = slots.clone();
+            bytes[1] = *val;
+            assert!(invalid(&bytes));
+        }
+        let mut bytes = slots;
+        bytes[41] = 0x10;
+        assert!(invalid(&bytes));
+
+        // code left after the last slot
+        let grid = Generator::new(1).solution(&Board::new()).unwrap();
+        let mut bytes = grid.to_bytes().unwrap();
+        bytes[16] |= 0x80;
+        assert!(invalid(&bytes));
+    }
+
     #[test]
     fn from_str() {
         let sudoku =
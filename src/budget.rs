@@ -0,0 +1,242 @@
+//! limit how much work a search may do.
+//!
+//! ```
+//! use sudoku_rs::{board::Board, budget::Budget, error::SuDoKuError, solver::DfsSolver};
+//!
+//! let mut b = Board::new();
+//! let s = DfsSolver::with_budget(Budget::new().max_nodes(10));
+//! assert!(matches!(s.solve(&mut b), Err(SuDoKuError::BudgetExhausted)));
+//! ```
+use crate::error::SuDoKuError;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// the deadline is checked once every this many nodes.
+#[cfg(feature = "std")]
+const DEADLINE_INTERVAL: usize = 256;
+
+/// cooperative cancellation flag.
+/// clones share the same flag, so it can be cancelled from another thread.
+/// only on targets with pointer sized atomics.
+/// ```
+/// use sudoku_rs::budget::CancelToken;
+///
+/// let token = CancelToken::new();
+/// let other = token.clone();
+/// std::thread::spawn(move || other.cancel()).join().unwrap();
+/// assert!(token.is_cancelled());
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Default, Clone, Debug)]
+pub struct CancelToken {
+    inner: Arc<AtomicBool>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl CancelToken {
+    /// return a new token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ask every search holding this token to stop.
+    pub fn cancel(&self) {
+        self.inner.store(true, Ordering::Relaxed);
+    }
+
+    /// check if cancel was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.load(Ordering::Relaxed)
+    }
+}
+
+/// the limits of one search.
+/// a search out of budget fails with `SuDoKuError::BudgetExhausted`.
+/// default budget is unlimited.
+/// deadlines need the `std` feature.
+#[derive(Default, Clone, Debug)]
+pub struct Budget {
+    max_nodes: Option<usize>,
+    #[cfg(feature = "std")]
+    deadline: Option<Instant>,
+    #[cfg(feature = "std")]
+    timeout: Option<Duration>,
+    #[cfg(target_has_atomic = "ptr")]
+    cancel: Option<CancelToken>,
+}
+
+impl Budget {
+    /// return an unlimited budget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// visit at most `nodes` nodes.
+    pub fn max_nodes(mut self, nodes: usize) -> Self {
+        self.max_nodes = Some(nodes);
+        self
+    }
+
+    /// stop after `deadline`.
+    #[cfg(feature = "std")]
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// stop after `timeout` from the start of each search.
+    #[cfg(feature = "std")]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// stop when `token` is cancelled.
+    #[cfg(target_has_atomic = "ptr")]
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// start a search, a timeout counts from now.
+    pub(crate) fn meter(&self) -> Meter<'_> {
+        Meter {
+            budget: self,
+            nodes: 0,
+            #[cfg(feature = "std")]
+            deadline: self.deadline_from_now(),
+        }
+    }
+
+    /// the earlier of the deadline and the timeout from now.
+    #[cfg(feature = "std")]
+    fn deadline_from_now(&self) -> Option<Instant> {
+        let timeout = self.timeout.map(|timeout| Instant::now() + timeout);
+        match (self.deadline, timeout) {
+            (Some(deadline), Some(timeout)) => Some(deadline.min(timeout)),
+            (deadline, timeout) => deadline.or(timeout),
+        }
+    }
+
+    /// the same budget with a timeout from now turned into a deadline,
+    /// for a job made of many searches.
+    pub(crate) fn started(&self) -> Self {
+        #[allow(unused_mut)]
+        let mut budget = self.clone();
+        #[cfg(feature = "std")]
+        {
+            budget.deadline = self.deadline_from_now();
+            budget.timeout = None;
+        }
+        budget
+    }
+
+    /// the same budget with at most `nodes` nodes.
+    pub(crate) fn capped(&self, nodes: usize) -> Self {
+        let mut budget = self.clone();
+        budget.max_nodes = Some(self.max_nodes.map_or(nodes, |max| max.min(nodes)));
+        budget
+    }
+
+    /// check if the deadline is passed or the token is cancelled, nodes are not counted.
+    pub(crate) fn is_exhausted(&self) -> bool {
+        #[cfg(feature = "std")]
+        if matches!(self.deadline, Some(deadline) if Instant::now() >= deadline) {
+            return true;
+        }
+        #[cfg(target_has_atomic = "ptr")]
+        if matches!(&self.cancel, Some(cancel) if cancel.is_cancelled()) {
+            return true;
+        }
+        false
+    }
+}
+
+/// spend a budget node by node.
+pub(crate) struct Meter<'a> {
+    budget: &'a Budget,
+    nodes: usize,
+    #[cfg(feature = "std")]
+    deadline: Option<Instant>,
+}
+
+impl Meter<'_> {
+    /// count a node, fail if the budget is exhausted.
+    pub fn tick(&mut self) -> Result<(), SuDoKuError> {
+        self.nodes += 1;
+        if let Some(max_nodes) = self.budget.max_nodes {
+            if self.nodes > max_nodes {
+                return Err(SuDoKuError::BudgetExhausted);
+            }
+        }
+        #[cfg(target_has_atomic = "ptr")]
+        if let Some(cancel) = &self.budget.cancel {
+            if cancel.is_cancelled() {
+                return Err(SuDoKuError::BudgetExhausted);
+            }
+        }
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.deadline {
+            if self.nodes % DEADLINE_INTERVAL == 1 && Instant::now() >= deadline {
+                return Err(SuDoKuError::BudgetExhausted);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unlimited() {
+        let budget = Budget::new();
+        let mut meter = budget.meter();
+        for _ in 0..1000 {
+            meter.tick().unwrap();
+        }
+    }
+
+    #[test]
+    fn max_nodes() {
+        let budget = Budget::new().max_nodes(2);
+        let mut meter = budget.meter();
+        assert!(meter.tick().is_ok());
+        assert!(meter.tick().is_ok());
+        assert!(meter.tick().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn deadline() {
+        let budget = Budget::new().deadline(Instant::now());
+        assert!(budget.meter().tick().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn timeout() {
+        let budget = Budget::new().timeout(Duration::from_millis(20));
+        std::thread::sleep(Duration::from_millis(40));
+        let mut meter = budget.meter();
+        assert!(meter.tick().is_ok());
+        std::thread::sleep(Duration::from_millis(40));
+        assert!((0..DEADLINE_INTERVAL).any(|_| meter.tick().is_err()));
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn cancel() {
+        let token = CancelToken::new();
+        let budget = Budget::new().cancel_token(token.clone());
+        let mut meter = budget.meter();
+        assert!(meter.tick().is_ok());
+        token.cancel();
+        assert!(meter.tick().is_err());
+    }
+}
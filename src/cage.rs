@@ -0,0 +1,159 @@
+//! killer sudoku cages.
+//!
+//! a cage is a group of slots whose values must add up to a given sum,
+//! and no value repeats inside a cage.
+//! ```
+//! use sudoku_rs::{board::Board, cage::Cage};
+//!
+//! let mut b = Board::new();
+//! b.add_cage(Cage::new(3, vec![(0, 0), (0, 1)]).unwrap()).unwrap();
+//! assert_eq!(b.avaliable_val(0, 0), vec![1, 2]);
+//! ```
+//! in the text format a cage is the sum and its slots in `r1c1` notation.
+//! ```
+//! use sudoku_rs::{board::Board, cage::Cage};
+//!
+//! let cage: Cage = "17 r1c1 r2c1".parse().unwrap();
+//! assert_eq!(cage.sum(), 17);
+//! assert_eq!(cage.slots(), &[(0, 0), (1, 0)]);
+//! assert_eq!(cage.to_string(), "17 r1c1 r2c1");
+//!
+//! let b: Board = "cage: 17 r1c1 r2c1".parse().unwrap();
+//! assert_eq!(b.avaliable_val(1, 0), vec![8, 9]);
+//! ```
+use crate::{bitset::CandidateSet, error::SuDoKuError, prelude::*};
+use core::fmt;
+
+/// a group of slots which sum up to `sum` with no repeat.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cage {
+    sum: u8,
+    slots: Vec<(usize, usize)>,
+}
+
+impl Cage {
+    /// return a new cage.
+    /// slots must be distinct and on board, and the sum must be reachable.
+    pub fn new(sum: u8, slots: Vec<(usize, usize)>) -> Result<Self, SuDoKuError> {
+        if slots.is_empty() || slots.len() > 9 {
+            return Err(SuDoKuError::InvalidCage);
+        }
+        for (i, &(x, y)) in slots.iter().enumerate() {
+            if x >= 9 || y >= 9 {
+                return Err(SuDoKuError::OutOfBound);
+            }
+            if slots[..i].contains(&(x, y)) {
+                return Err(SuDoKuError::InvalidCage);
+            }
+        }
+        if combinations(sum, slots.len(), CandidateSet::new()).count() == 0 {
+            return Err(SuDoKuError::InvalidCage);
+        }
+        Ok(Cage { sum, slots })
+    }
+
+    /// the sum of the cage.
+    pub fn sum(&self) -> u8 {
+        self.sum
+    }
+
+    /// the slots of the cage.
+    pub fn slots(&self) -> &[(usize, usize)] {
+        &self.slots
+    }
+}
+
+/// the values which can still be used by the empty slots of a cage.
+/// `sum` and `count` are what left for the empty slots, `used` is the values placed.
+pub(crate) fn combinations(sum: u8, count: usize, used: CandidateSet) -> CandidateSet {
+    let mut allowed = 0;
+    // every subset of 1..=9, bit 0 is never used
+    for mask in (0u16..512).map(|m| m << 1) {
+        if mask & used.bits() == 0
+            && mask.count_ones() as usize == count
+            && CandidateSet::from_bits(mask).sum() == sum
+        {
+            allowed |= mask;
+        }
+    }
+    CandidateSet::from_bits(allowed)
+}
+
+impl fmt::Display for Cage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.sum)?;
+        for (x, y) in self.slots.iter() {
+            write!(f, " r{}c{}", x + 1, y + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for Cage {
+    type Err = SuDoKuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let sum = words
+            .next()
+            .and_then(|word| word.parse().ok())
+            .ok_or(SuDoKuError::InvalidFormat)?;
+        let slots = words.map(parse_slot).collect::<Result<_, _>>()?;
+        Cage::new(sum, slots)
+    }
+}
+
+/// parse a slot in `r1c1` notation.
+pub(crate) fn parse_slot(word: &str) -> Result<(usize, usize), SuDoKuError> {
+    let rest = word.strip_prefix('r').ok_or(SuDoKuError::InvalidFormat)?;
+    let (row, col) = rest.split_once('c').ok_or(SuDoKuError::InvalidFormat)?;
+    let row: usize = row.parse().map_err(|_| SuDoKuError::InvalidFormat)?;
+    let col: usize = col.parse().map_err(|_| SuDoKuError::InvalidFormat)?;
+    if !(1..=9).contains(&row) || !(1..=9).contains(&col) {
+        return Err(SuDoKuError::OutOfBound);
+    }
+    Ok((row - 1, col - 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn combination() {
+        assert_eq!(
+            combinations(3, 2, CandidateSet::new())
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(combinations(45, 9, CandidateSet::new()).count(), 9);
+        assert_eq!(combinations(10, 0, CandidateSet::new()).count(), 0);
+        assert_eq!(combinations(0, 0, CandidateSet::new()).count(), 0);
+        let mut used = CandidateSet::new();
+        used.set(1).unwrap();
+        // 7 in two slots without 1: 2 + 5 or 3 + 4
+        assert_eq!(
+            combinations(7, 2, used).iter().collect::<Vec<_>>(),
+            vec![2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Cage::new(2, vec![(0, 0), (0, 1)]).is_err());
+        assert!(Cage::new(3, vec![(0, 0), (0, 0)]).is_err());
+        assert!(Cage::new(3, vec![(0, 0), (0, 9)]).is_err());
+        assert!(Cage::new(3, vec![]).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let cage = Cage::new(10, vec![(8, 8), (8, 7), (7, 8)]).unwrap();
+        let text = cage.to_string();
+        assert_eq!(text, "10 r9c9 r9c8 r8c9");
+        assert_eq!(text.parse::<Cage>().unwrap(), cage);
+        assert!("10 r0c1".parse::<Cage>().is_err());
+        assert!("10 x1c1".parse::<Cage>().is_err());
+    }
+}
@@ -0,0 +1,325 @@
+//! c bindings, built with the `capi` feature.
+//!
+//! a board is an opaque `Board` handle, made by `sudoku_board_new`, `sudoku_board_parse`
+//! or `sudoku_generate`, and released by `sudoku_board_free`.
+//! every function which can fail returns `SUDOKU_OK` or an error code,
+//! results are written through the out pointers.
+//! ```c
+//! #include "sudoku_rs.h"
+//!
+//! Board *board = NULL;
+//! if (sudoku_board_parse("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......", &board) == SUDOKU_OK) {
+//!     sudoku_solve(board, 0);
+//!     uint8_t cells[81];
+//!     sudoku_board_cells(board, cells);
+//!     sudoku_board_free(board);
+//! }
+//! ```
+//! the header is `include/sudoku_rs.h`, generated by `cbindgen`.
+//! build the libraries with
+//! `cargo rustc --release --lib --crate-type cdylib --features capi`
+//! and `--crate-type staticlib`.
+use crate::{
+    board::Board, budget::Budget, error::SuDoKuError, generator::Generator, grade::Grade,
+    solver::DfsSolver,
+};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+/// success.
+pub const SUDOKU_OK: c_int = 0;
+/// `SuDoKuError::InvalidValue`.
+pub const SUDOKU_INVALID_VALUE: c_int = 1;
+/// `SuDoKuError::OutOfBound`.
+pub const SUDOKU_OUT_OF_BOUND: c_int = 2;
+/// `SuDoKuError::NotSolveable`.
+pub const SUDOKU_NOT_SOLVEABLE: c_int = 3;
+/// `SuDoKuError::DuplicateValue`.
+pub const SUDOKU_DUPLICATE_VALUE: c_int = 4;
+/// `SuDoKuError::BudgetExhausted`.
+pub const SUDOKU_BUDGET_EXHAUSTED: c_int = 5;
+/// `SuDoKuError::InvalidFormat`.
+pub const SUDOKU_INVALID_FORMAT: c_int = 6;
+/// `SuDoKuError::InvalidRegion`.
+pub const SUDOKU_INVALID_REGION: c_int = 7;
+/// `SuDoKuError::InvalidCage`.
+pub const SUDOKU_INVALID_CAGE: c_int = 8;
+/// a pointer argument is null.
+pub const SUDOKU_NULL_POINTER: c_int = 9;
+/// the call panicked, which is a bug of this crate.
+pub const SUDOKU_PANIC: c_int = 10;
+/// `SuDoKuError::GivensNotReached`.
+pub const SUDOKU_GIVENS_NOT_REACHED: c_int = 11;
+
+/// the score of `sudoku_grade`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SudokuScore {
+    pub dfs_complexity: usize,
+    pub empty_slot_count: usize,
+}
+
+fn code(err: SuDoKuError) -> c_int {
+    match err {
+        SuDoKuError::InvalidValue => SUDOKU_INVALID_VALUE,
+        SuDoKuError::OutOfBound => SUDOKU_OUT_OF_BOUND,
+        SuDoKuError::NotSolveable => SUDOKU_NOT_SOLVEABLE,
+        SuDoKuError::DuplicateValue => SUDOKU_DUPLICATE_VALUE,
+        SuDoKuError::BudgetExhausted => SUDOKU_BUDGET_EXHAUSTED,
+        SuDoKuError::InvalidFormat => SUDOKU_INVALID_FORMAT,
+        SuDoKuError::InvalidRegion => SUDOKU_INVALID_REGION,
+        SuDoKuError::InvalidCage => SUDOKU_INVALID_CAGE,
+        SuDoKuError::GivensNotReached => SUDOKU_GIVENS_NOT_REACHED,
+    }
+}
+
+/// run a call, a panic must not unwind into c.
+fn guard<F: FnOnce() -> Result<(), c_int>>(f: F) -> c_int {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => SUDOKU_OK,
+        Ok(Err(code)) => code,
+        Err(_) => SUDOKU_PANIC,
+    }
+}
+
+/// 0 nodes is unlimited.
+fn budget(max_nodes: usize) -> Budget {
+    match max_nodes {
+        0 => Budget::new(),
+        nodes => Budget::new().max_nodes(nodes),
+    }
+}
+
+unsafe fn board_ref<'a>(board: *const Board) -> Result<&'a Board, c_int> {
+    board.as_ref().ok_or(SUDOKU_NULL_POINTER)
+}
+
+unsafe fn write<T>(out: *mut T, val: T) -> Result<(), c_int> {
+    if out.is_null() {
+        return Err(SUDOKU_NULL_POINTER);
+    }
+    out.write(val);
+    Ok(())
+}
+
+/// return a new empty classic board.
+#[no_mangle]
+pub extern "C" fn sudoku_board_new() -> *mut Board {
+    Box::into_raw(Box::new(Board::new()))
+}
+
+/// parse a board in the text format of `Board`, variants included.
+///
+/// # Safety
+/// `text` is a nul terminated utf-8 string, `out` is null or points to a handle.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_board_parse(text: *const c_char, out: *mut *mut Board) -> c_int {
+    guard(|| {
+        // check `out` before parsing, the board would leak without a place to go
+        if text.is_null() || out.is_null() {
+            return Err(SUDOKU_NULL_POINTER);
+        }
+        let text = CStr::from_ptr(text)
+            .to_str()
+            .map_err(|_| SUDOKU_INVALID_FORMAT)?;
+        let board: Board = text.parse().map_err(code)?;
+        write(out, Box::into_raw(Box::new(board)))
+    })
+}
+
+/// release a board, null is ignored.
+///
+/// # Safety
+/// `board` is null or a handle not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_board_free(board: *mut Board) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
+
+/// write the 81 values of a board, 0 for empty.
+///
+/// # Safety
+/// `board` is a handle, `cells` points to 81 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_board_cells(board: *const Board, cells: *mut u8) -> c_int {
+    guard(|| {
+        let board = board_ref(board)?;
+        for pos in 0..81 {
+            let val = board.unchecked_get(pos / 9, pos % 9).unwrap_or(0);
+            write(cells.wrapping_add(pos), val)?;
+        }
+        Ok(())
+    })
+}
+
+/// return a board in the text format, release it with `sudoku_string_free`.
+/// null if `board` is null.
+///
+/// # Safety
+/// `board` is null or a handle.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_board_to_string(board: *const Board) -> *mut c_char {
+    match board.as_ref() {
+        // the text format never has a nul
+        Some(board) => CString::new(board.to_string())
+            .map(CString::into_raw)
+            .unwrap_or(ptr::null_mut()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// release a string of `sudoku_board_to_string`, null is ignored.
+///
+/// # Safety
+/// `text` is null or a string of `sudoku_board_to_string` not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// solve a board in place, visit at most `max_nodes` nodes, 0 for unlimited.
+///
+/// # Safety
+/// `board` is a handle.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_solve(board: *mut Board, max_nodes: usize) -> c_int {
+    guard(|| {
+        let board = board.as_mut().ok_or(SUDOKU_NULL_POINTER)?;
+        DfsSolver::with_budget(budget(max_nodes))
+            .solve(board)
+            .map(|_| ())
+            .map_err(code)
+    })
+}
+
+/// check if the solve of a board is unique, the board is kept as it is.
+///
+/// # Safety
+/// `board` is a handle, `unique` points to a bool.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_unique(
+    board: *const Board,
+    max_nodes: usize,
+    unique: *mut bool,
+) -> c_int {
+    guard(|| {
+        let mut board = board_ref(board)?.clone();
+        let res = DfsSolver::with_budget(budget(max_nodes))
+            .unique(&mut board)
+            .map_err(code)?;
+        write(unique, res)
+    })
+}
+
+/// grade a board, the board is kept as it is.
+///
+/// # Safety
+/// `board` is a handle, `score` points to a score.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_grade(
+    board: *const Board,
+    max_nodes: usize,
+    score: *mut SudokuScore,
+) -> c_int {
+    guard(|| {
+        let mut board = board_ref(board)?.clone();
+        let res = Grade::with_budget(budget(max_nodes))
+            .try_grade(&mut board)
+            .map_err(code)?;
+        write(
+            score,
+            SudokuScore {
+                dfs_complexity: res.dfs_complexity,
+                empty_slot_count: res.empty_slot_count,
+            },
+        )
+    })
+}
+
+/// generate a classic puzzle with a unique solve, reproducible with the same seed.
+///
+/// # Safety
+/// `out` is null or points to a handle.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_generate(seed: u64, out: *mut *mut Board) -> c_int {
+    guard(|| {
+        if out.is_null() {
+            return Err(SUDOKU_NULL_POINTER);
+        }
+        let board = Generator::new(seed).generate(&Board::new()).map_err(code)?;
+        write(out, Box::into_raw(Box::new(board)))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EASY: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+
+    #[test]
+    fn round_trip() {
+        let text = CString::new(EASY).unwrap();
+        let mut board = ptr::null_mut();
+        unsafe {
+            assert_eq!(sudoku_board_parse(text.as_ptr(), &mut board), SUDOKU_OK);
+            let mut unique = false;
+            assert_eq!(sudoku_unique(board, 0, &mut unique), SUDOKU_OK);
+            assert!(unique);
+            let mut score = SudokuScore::default();
+            assert_eq!(sudoku_grade(board, 0, &mut score), SUDOKU_OK);
+            assert_eq!(score.empty_slot_count, 54);
+            assert_eq!(sudoku_solve(board, 0), SUDOKU_OK);
+            let mut cells = [0; 81];
+            assert_eq!(sudoku_board_cells(board, cells.as_mut_ptr()), SUDOKU_OK);
+            assert!(cells.iter().all(|&val| val != 0));
+            let text = sudoku_board_to_string(board);
+            assert_eq!(CStr::from_ptr(text).to_str().unwrap(), (*board).to_string());
+            sudoku_string_free(text);
+            sudoku_board_free(board);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let bad = CString::new("variant: nothing").unwrap();
+        let mut board = ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                sudoku_board_parse(bad.as_ptr(), &mut board),
+                SUDOKU_INVALID_FORMAT
+            );
+            assert!(board.is_null());
+            assert_eq!(sudoku_solve(ptr::null_mut(), 0), SUDOKU_NULL_POINTER);
+            let empty = sudoku_board_new();
+            let mut unique = true;
+            assert_eq!(sudoku_unique(empty, 0, &mut unique), SUDOKU_OK);
+            assert!(!unique);
+            assert_eq!(sudoku_solve(empty, 3), SUDOKU_BUDGET_EXHAUSTED);
+            assert_eq!(
+                sudoku_unique(empty, 0, ptr::null_mut()),
+                SUDOKU_NULL_POINTER
+            );
+            sudoku_board_free(empty);
+            let text = CString::new(EASY).unwrap();
+            assert_eq!(
+                sudoku_board_parse(text.as_ptr(), ptr::null_mut()),
+                SUDOKU_NULL_POINTER
+            );
+            assert_eq!(sudoku_generate(3, ptr::null_mut()), SUDOKU_NULL_POINTER);
+            assert_eq!(sudoku_generate(3, &mut board), SUDOKU_OK);
+            assert_eq!(sudoku_unique(board, 0, &mut unique), SUDOKU_OK);
+            assert!(unique);
+            sudoku_board_free(board);
+        }
+    }
+}
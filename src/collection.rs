@@ -0,0 +1,555 @@
+//! a collection of classic puzzles with their metadata, kept in a compact file.
+//!
+//! puzzles are deduplicated by their canonical form, see `symmetry`,
+//! and can be found by grade, clue count or tag.
+//! ```
+//! use sudoku_rs::{budget::Budget, collection::{Collection, Entry}};
+//!
+//! let mut c = Collection::new();
+//! let puzzles = "\
+//! 200007030800000009701005048000400001000500273010379006070030000000002590600000000
+//! 100007030800000009702005048000400002000500173020379006070030000000001590600000000
+//! ";
+//! // the second line is the first with 1 and 2 swapped
+//! assert_eq!(c.import(puzzles.as_bytes(), "newspaper").unwrap(), 1);
+//! let board = "000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+//! let entry = Entry::new(board.parse().unwrap()).unwrap()
+//!     .with_tag("17 clues")
+//!     .analyze(Budget::new().max_nodes(1_000_000))
+//!     .unwrap();
+//! assert!(c.insert(entry).unwrap());
+//! assert_eq!(c.by_clues(..20).count(), 1);
+//! assert_eq!(c.with_tag("17 clues").count(), 1);
+//! let mut lines = Vec::new();
+//! c.export(&mut lines).unwrap();
+//! ```
+//! the file starts with `SDKC` and a version byte, then one record per puzzle:
+//!
+//! | bytes      | field                                            |
+//! |------------|--------------------------------------------------|
+//! | 2          | length of the rest of the record, little endian  |
+//! | 1 + n      | the puzzle, see `Board::to_bytes`                |
+//! | 1          | flags, 1 for a solution, 2 for a grade           |
+//! | 1 + n      | the solution, if flagged                         |
+//! | 4          | the dfs complexity, if flagged                   |
+//! | 8          | the canonical hash                               |
+//! | 1 + n      | the source                                       |
+//! | 1 + n each | the count of tags, then the tags                 |
+//!
+//! the clue count is not stored, it is counted on load.
+use crate::{
+    board::Board, budget::Budget, error::SuDoKuError, grade::Grade, solver::DfsSolver, symmetry,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, ErrorKind, Read, Write},
+    ops::RangeBounds,
+    path::Path,
+};
+
+const MAGIC: &[u8; 4] = b"SDKC";
+const VERSION: u8 = 2;
+const HAS_SOLUTION: u8 = 1;
+const HAS_GRADE: u8 = 2;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// a board in the bytes format after its length.
+fn push_board(board: &Board, out: &mut Vec<u8>) -> io::Result<()> {
+    let bytes = board
+        .to_bytes()
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "a board is not classic"))?;
+    out.push(bytes.len() as u8);
+    out.extend_from_slice(&bytes);
+    Ok(())
+}
+
+fn push_text(text: &str, out: &mut Vec<u8>) -> io::Result<()> {
+    if text.len() > u8::MAX as usize {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "a source or tag is longer than 255 bytes",
+        ));
+    }
+    out.push(text.len() as u8);
+    out.extend_from_slice(text.as_bytes());
+    Ok(())
+}
+
+/// read a record field by field.
+struct Fields<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(invalid("a record is too short"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn board(&mut self) -> io::Result<Board> {
+        let len = self.byte()? as usize;
+        Board::from_bytes(self.take(len)?).map_err(|_| invalid("a record has a bad board"))
+    }
+
+    fn text(&mut self) -> io::Result<String> {
+        let len = self.byte()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("a text is not utf-8"))
+    }
+}
+
+/// a classic puzzle and what is known about it.
+#[derive(Clone)]
+pub struct Entry {
+    puzzle: Board,
+    solution: Option<Board>,
+    grade: Option<usize>,
+    clues: u8,
+    hash: u64,
+    source: String,
+    tags: Vec<String>,
+}
+
+impl Entry {
+    /// return a new entry without solution, grade, source and tags.
+    /// fail with `SuDoKuError::InvalidFormat` if the puzzle is not classic.
+    pub fn new(puzzle: Board) -> Result<Self, SuDoKuError> {
+        let hash = symmetry::canonical_hash(&puzzle)?;
+        let clues = (0..81)
+            .filter(|&pos| !puzzle.is_empty(pos / 9, pos % 9))
+            .count() as u8;
+        Ok(Entry {
+            puzzle,
+            solution: None,
+            grade: None,
+            clues,
+            hash,
+            source: String::new(),
+            tags: Vec::new(),
+        })
+    }
+
+    /// where the puzzle comes from, at most 255 bytes.
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = source.to_string();
+        self
+    }
+
+    /// add a tag, at most 255 bytes and 255 tags, an entry is at most 65535 bytes.
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        if !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
+        }
+        self
+    }
+
+    pub fn with_solution(mut self, solution: Board) -> Self {
+        self.solution = Some(solution);
+        self
+    }
+
+    /// the dfs complexity of `Grade`.
+    pub fn with_grade(mut self, grade: usize) -> Self {
+        self.grade = Some(grade);
+        self
+    }
+
+    /// solve and grade the puzzle, each of them within `budget`.
+    pub fn analyze(self, budget: Budget) -> Result<Self, SuDoKuError> {
+        let mut solution = self.puzzle.clone();
+        DfsSolver::with_budget(budget.clone()).solve(&mut solution)?;
+        let score = Grade::with_budget(budget).try_grade(&mut self.puzzle.clone())?;
+        Ok(self
+            .with_solution(solution)
+            .with_grade(score.dfs_complexity))
+    }
+
+    pub fn puzzle(&self) -> &Board {
+        &self.puzzle
+    }
+
+    pub fn solution(&self) -> Option<&Board> {
+        self.solution.as_ref()
+    }
+
+    /// the dfs complexity of `Grade`.
+    pub fn grade(&self) -> Option<usize> {
+        self.grade
+    }
+
+    pub fn clues(&self) -> u8 {
+        self.clues
+    }
+
+    /// the `symmetry::canonical_hash` of the puzzle.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// the puzzle in one line of 81 values, 0 for empty.
+    pub fn line(&self) -> String {
+        (0..81)
+            .map(|pos| {
+                let val = self.puzzle.unchecked_get(pos / 9, pos % 9).unwrap_or(0);
+                char::from(b'0' + val)
+            })
+            .collect()
+    }
+
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        if self.tags.len() > u8::MAX as usize {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "an entry has more than 255 tags",
+            ));
+        }
+        let mut record = vec![0, 0];
+        push_board(&self.puzzle, &mut record)?;
+        let mut flags = 0;
+        if self.solution.is_some() {
+            flags |= HAS_SOLUTION;
+        }
+        if self.grade.is_some() {
+            flags |= HAS_GRADE;
+        }
+        record.push(flags);
+        if let Some(solution) = &self.solution {
+            push_board(solution, &mut record)?;
+        }
+        if let Some(grade) = self.grade {
+            let grade = grade.min(u32::MAX as usize) as u32;
+            record.extend_from_slice(&grade.to_le_bytes());
+        }
+        record.extend_from_slice(&self.hash.to_le_bytes());
+        push_text(&self.source, &mut record)?;
+        record.push(self.tags.len() as u8);
+        for tag in self.tags.iter() {
+            push_text(tag, &mut record)?;
+        }
+        if record.len() - 2 > u16::MAX as usize {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "an entry is longer than 65535 bytes",
+            ));
+        }
+        let len = (record.len() - 2) as u16;
+        record[..2].copy_from_slice(&len.to_le_bytes());
+        Ok(record)
+    }
+
+    /// a record without its length.
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut fields = Fields { bytes };
+        let puzzle = fields.board()?;
+        let flags = fields.byte()?;
+        let solution = match flags & HAS_SOLUTION {
+            0 => None,
+            _ => Some(fields.board()?),
+        };
+        let grade = match flags & HAS_GRADE {
+            0 => None,
+            _ => {
+                let mut grade = [0; 4];
+                grade.copy_from_slice(fields.take(4)?);
+                Some(u32::from_le_bytes(grade) as usize)
+            }
+        };
+        let mut hash = [0; 8];
+        hash.copy_from_slice(fields.take(8)?);
+        let source = fields.text()?;
+        let tags = (0..fields.byte()?)
+            .map(|_| fields.text())
+            .collect::<io::Result<_>>()?;
+        let clues = (0..81)
+            .filter(|&pos| !puzzle.is_empty(pos / 9, pos % 9))
+            .count() as u8;
+        Ok(Entry {
+            puzzle,
+            solution,
+            grade,
+            clues,
+            hash: u64::from_le_bytes(hash),
+            source,
+            tags,
+        })
+    }
+}
+
+/// puzzles indexed by canonical hash, grade and clue count.
+/// a collection from `open` writes every insert to its file.
+#[derive(Default)]
+pub struct Collection {
+    entries: Vec<Entry>,
+    by_hash: HashMap<u64, Vec<usize>>,
+    by_grade: BTreeMap<usize, Vec<usize>>,
+    by_clues: BTreeMap<u8, Vec<usize>>,
+    file: Option<File>,
+}
+
+impl Collection {
+    /// return a new collection in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// open the collection in a file, create the file if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let mut collection = if file.metadata()?.len() == 0 {
+            file.write_all(MAGIC)?;
+            file.write_all(&[VERSION])?;
+            Collection::new()
+        } else {
+            Collection::read_from(&mut file)?
+        };
+        collection.file = Some(file);
+        Ok(collection)
+    }
+
+    /// read a collection in the file format, it is kept in memory.
+    pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        io::BufReader::new(reader).read_to_end(&mut bytes)?;
+        if bytes.len() < 5 || &bytes[..4] != MAGIC {
+            return Err(invalid("not a collection"));
+        }
+        if bytes[4] != VERSION {
+            return Err(invalid("unknown collection version"));
+        }
+        let mut collection = Collection::new();
+        let mut rest = &bytes[5..];
+        while !rest.is_empty() {
+            if rest.len() < 2 {
+                return Err(invalid("a record is too short"));
+            }
+            let len = u16::from_le_bytes([rest[0], rest[1]]) as usize;
+            if rest.len() < 2 + len {
+                return Err(invalid("a record is too short"));
+            }
+            collection.index(Entry::decode(&rest[2..2 + len])?);
+            rest = &rest[2 + len..];
+        }
+        Ok(collection)
+    }
+
+    /// write the whole collection in the file format.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        for entry in self.entries.iter() {
+            writer.write_all(&entry.encode()?)?;
+        }
+        writer.flush()
+    }
+
+    fn index(&mut self, entry: Entry) {
+        let index = self.entries.len();
+        self.by_hash.entry(entry.hash).or_default().push(index);
+        if let Some(grade) = entry.grade {
+            self.by_grade.entry(grade).or_default().push(index);
+        }
+        self.by_clues.entry(entry.clues).or_default().push(index);
+        self.entries.push(entry);
+    }
+
+    /// the entry of the same puzzle, up to symmetry.
+    pub fn find(&self, puzzle: &Board) -> Option<&Entry> {
+        let form = symmetry::minlex(puzzle).ok()?;
+        self.find_form(symmetry::hash(&form), || form)
+    }
+
+    /// the form is only made if the hash is known, it is the slow part.
+    fn find_form<F: FnOnce() -> [u8; 81]>(&self, hash: u64, form: F) -> Option<&Entry> {
+        let indexes = self.by_hash.get(&hash)?;
+        let form = form();
+        indexes
+            .iter()
+            .map(|&index| &self.entries[index])
+            // a hash may be shared by two puzzles
+            .find(|entry| symmetry::minlex(&entry.puzzle).ok() == Some(form))
+    }
+
+    /// add an entry, return false if the same puzzle is in already.
+    pub fn insert(&mut self, entry: Entry) -> io::Result<bool> {
+        let record = entry.encode()?;
+        let form = || symmetry::minlex(&entry.puzzle).unwrap_or([0; 81]);
+        if self.find_form(entry.hash, form).is_some() {
+            return Ok(false);
+        }
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(&record)?;
+        }
+        self.index(entry);
+        Ok(true)
+    }
+
+    /// add the puzzles of a text with one puzzle per line, as `Board::from_str` reads them.
+    /// empty lines and lines starting with `#` are skipped.
+    /// return how many puzzles were new.
+    pub fn import<R: BufRead>(&mut self, reader: R, source: &str) -> io::Result<usize> {
+        let mut count = 0;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = line
+                .parse()
+                .and_then(Entry::new)
+                .map_err(|e| invalid(&format!("line {}: {}", number + 1, e)))?;
+            if self.insert(entry.with_source(source))? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// write every puzzle in one line of 81 values.
+    pub fn export<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for entry in self.entries.iter() {
+            writeln!(writer, "{}", entry.line())?;
+        }
+        writer.flush()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// entries in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    /// entries with a grade in `range`, from easy to hard.
+    pub fn by_grade<R: RangeBounds<usize>>(&self, range: R) -> impl Iterator<Item = &Entry> {
+        self.by_grade
+            .range(range)
+            .flat_map(move |(_, indexes)| indexes.iter().map(move |&i| &self.entries[i]))
+    }
+
+    /// entries with a clue count in `range`, from few to many.
+    pub fn by_clues<R: RangeBounds<u8>>(&self, range: R) -> impl Iterator<Item = &Entry> {
+        self.by_clues
+            .range(range)
+            .flat_map(move |(_, indexes)| indexes.iter().map(move |&i| &self.entries[i]))
+    }
+
+    pub fn with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Entry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.tags.iter().any(|t| t == tag))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{env, fs, process};
+
+    const EASY: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+    const HARD: &str =
+        "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+
+    #[test]
+    fn file() {
+        let path = env::temp_dir().join(format!("sudoku-collection-{}", process::id()));
+        let _ = fs::remove_file(&path);
+        {
+            let mut c = Collection::open(&path).unwrap();
+            let easy = Entry::new(EASY.parse().unwrap())
+                .unwrap()
+                .with_source("newspaper")
+                .with_tag("easy")
+                .analyze(Budget::new())
+                .unwrap();
+            assert!(c.insert(easy).unwrap());
+            let hard = Entry::new(HARD.parse().unwrap()).unwrap().with_grade(70);
+            assert!(c.insert(hard).unwrap());
+        }
+        let c = Collection::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(c.len(), 2);
+        let easy = c.iter().next().unwrap();
+        assert_eq!(easy.line(), EASY);
+        assert_eq!(easy.source(), "newspaper");
+        assert_eq!(easy.tags(), ["easy".to_string()]);
+        assert!(easy.solution().unwrap().is_win());
+        assert_eq!(easy.clues(), 27);
+        assert_eq!(c.by_grade(70..=70).count(), 1);
+        assert_eq!(c.by_clues(..20).next().unwrap().line(), HARD);
+        assert!(c.find(&HARD.parse().unwrap()).is_some());
+    }
+
+    #[test]
+    fn dedup_and_errors() {
+        let mut c = Collection::new();
+        let text = format!("# puzzles\n{}\n\n{}\n{}\n", EASY, HARD, EASY);
+        assert_eq!(c.import(text.as_bytes(), "file").unwrap(), 2);
+        let mut bytes = Vec::new();
+        c.write_to(&mut bytes).unwrap();
+        let read = Collection::read_from(&bytes[..]).unwrap();
+        let mut lines = Vec::new();
+        read.export(&mut lines).unwrap();
+        assert_eq!(lines, format!("{}\n{}\n", EASY, HARD).into_bytes());
+        assert!(Collection::read_from(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Collection::read_from(&b"SDKC\x09"[..]).is_err());
+        assert!(c.import("variant: nothing".as_bytes(), "bad").is_err());
+        let long = "x".repeat(256);
+        let entry = Entry::new(EASY.parse().unwrap())
+            .unwrap()
+            .with_source(&long);
+        assert!(c.insert(entry).is_err());
+        assert!(Entry::new(Board::new_diagonal()).is_err());
+    }
+
+    #[test]
+    fn record_limit() {
+        let mut entry = Entry::new(EASY.parse().unwrap())
+            .unwrap()
+            .with_source(&"x".repeat(255))
+            .analyze(Budget::new())
+            .unwrap();
+        for i in 0..255 {
+            entry = entry.with_tag(&format!("{:0>255}", i));
+        }
+        let err = entry.encode().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let mut c = Collection::new();
+        assert!(c.insert(entry).is_err());
+        let mut short = Entry::new(EASY.parse().unwrap()).unwrap();
+        for i in 0..200 {
+            short = short.with_tag(&format!("{:0>255}", i));
+        }
+        assert!(c.insert(short).unwrap());
+    }
+}
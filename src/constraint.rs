@@ -0,0 +1,534 @@
+//! pluggable constraints for sudoku variants.
+//!
+//! a constraint restrict the values a slot can hold, it's consulted by
+//! `Board::avaliable_val` alongside rows, columns and regions.
+//! ```
+//! use sudoku_rs::{board::Board, constraint::AntiKnight};
+//!
+//! let mut b = Board::new();
+//! b.add_constraint(AntiKnight);
+//! b.set(0, 0, 1).unwrap();
+//! assert!(!b.avaliable_val(1, 2).contains(&1));
+//! ```
+//! in the text format the constraints are `key: value` lines too.
+//! ```
+//! use sudoku_rs::board::Board;
+//!
+//! let b: Board = "1\nvariant: antiking\ngreater: r1c2>r1c3".parse().unwrap();
+//! assert!(!b.avaliable_val(1, 1).contains(&1));
+//! assert_eq!(b.avaliable_val(0, 2), vec![2, 3, 4, 5, 6, 7, 8]);
+//! ```
+use crate::{board::Board, cage::parse_slot, error::SuDoKuError, prelude::*};
+#[cfg(not(target_has_atomic = "ptr"))]
+use alloc::rc::Rc as Arc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use core::fmt;
+
+mod adjacent;
+mod line;
+pub use adjacent::{Adjacent, Kropki, Mark, XV};
+pub use line::{Arrow, LittleKiller, Sandwich, Thermometer};
+
+/// a slot as `(x, y)`.
+pub type Pos = (usize, usize);
+
+/// a rule every solved board must follow.
+pub trait Constraint: fmt::Debug + Send + Sync {
+    /// check if `val` can be put on a slot, given the other values on board.
+    /// the value on the slot itself must be ignored.
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool;
+
+    /// check the values on board do not break the constraint.
+    /// work for both partial and completed board.
+    fn check(&self, board: &Board) -> bool {
+        (0..81).all(|pos| {
+            let (x, y) = (pos / 9, pos % 9);
+            board
+                .unchecked_get(x, y)
+                .map_or(true, |val| self.allows(board, x, y, val))
+        })
+    }
+
+    /// the `key: value` line in the text format, if it has one.
+    fn directive(&self) -> Option<String> {
+        None
+    }
+
+    /// check if the constraint only forbids single values and pairs of values,
+    /// then `sat::Cnf::encode` encodes it exactly.
+    fn pairwise(&self) -> bool {
+        false
+    }
+}
+
+/// parse a `key: value` line into a constraint.
+/// return `None` if the key is not a constraint.
+pub(crate) fn parse_directive(
+    key: &str,
+    value: &str,
+) -> Option<Result<Arc<dyn Constraint>, SuDoKuError>> {
+    let constraint: Arc<dyn Constraint> = match (key, value) {
+        ("variant", "antiknight") => Arc::new(AntiKnight),
+        ("variant", "antiking") => Arc::new(AntiKing),
+        ("variant", "nonconsecutive") => Arc::new(NonConsecutive),
+        ("variant", "disjoint") => Arc::new(DisjointGroups),
+        ("variant", "windoku") => Arc::new(Windoku),
+        ("even", value) => {
+            return Some(
+                parse_slots(value)
+                    .and_then(Parity::even)
+                    .map(|c| Arc::new(c) as _),
+            )
+        }
+        ("odd", value) => {
+            return Some(
+                parse_slots(value)
+                    .and_then(Parity::odd)
+                    .map(|c| Arc::new(c) as _),
+            )
+        }
+        ("greater", value) => return Some(value.parse::<GreaterThan>().map(|c| Arc::new(c) as _)),
+        ("kropki", value) => {
+            return Some(value.parse::<Adjacent<Kropki>>().map(|c| Arc::new(c) as _))
+        }
+        ("xv", value) => return Some(value.parse::<Adjacent<XV>>().map(|c| Arc::new(c) as _)),
+        ("thermo", value) => {
+            return Some(
+                parse_slots(value)
+                    .and_then(Thermometer::new)
+                    .map(|c| Arc::new(c) as _),
+            )
+        }
+        ("arrow", value) => return Some(line::parse_arrow(value).map(|c| Arc::new(c) as _)),
+        ("sandwich", value) => return Some(value.parse::<Sandwich>().map(|c| Arc::new(c) as _)),
+        ("littlekiller", value) => {
+            return Some(value.parse::<LittleKiller>().map(|c| Arc::new(c) as _))
+        }
+        _ => return None,
+    };
+    Some(Ok(constraint))
+}
+
+/// check every slot is on board.
+fn check_slots(slots: &[Pos]) -> Result<(), SuDoKuError> {
+    if slots.iter().any(|&(x, y)| x >= 9 || y >= 9) {
+        return Err(SuDoKuError::OutOfBound);
+    }
+    Ok(())
+}
+
+fn parse_slots(value: &str) -> Result<Vec<Pos>, SuDoKuError> {
+    value.split_whitespace().map(parse_slot).collect()
+}
+
+fn fmt_slot((x, y): Pos) -> String {
+    format!("r{}c{}", x + 1, y + 1)
+}
+
+/// check no slot at one of `offsets` from (x, y) holds `val`.
+fn differ_at(board: &Board, x: usize, y: usize, val: u8, offsets: &[(isize, isize)]) -> bool {
+    offsets.iter().all(|&(dx, dy)| {
+        let nx = x as isize + dx;
+        let ny = y as isize + dy;
+        !(0..9).contains(&nx)
+            || !(0..9).contains(&ny)
+            || board.unchecked_get(nx as usize, ny as usize) != Some(val)
+    })
+}
+
+const KNIGHT: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+const KING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+pub(crate) const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// slots a chess knight's move apart can not hold the same value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AntiKnight;
+
+impl Constraint for AntiKnight {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        differ_at(board, x, y, val, &KNIGHT)
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some("variant: antiknight".to_string())
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+/// slots a chess king's move apart can not hold the same value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AntiKing;
+
+impl Constraint for AntiKing {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        differ_at(board, x, y, val, &KING)
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some("variant: antiking".to_string())
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+/// orthogonally adjacent slots can not hold consecutive values.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NonConsecutive;
+
+impl Constraint for NonConsecutive {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        differ_at(board, x, y, val + 1, &ORTHOGONAL) && differ_at(board, x, y, val - 1, &ORTHOGONAL)
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some("variant: nonconsecutive".to_string())
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+/// slots at the same place of their 3x3 box can not hold the same value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DisjointGroups;
+
+impl Constraint for DisjointGroups {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        (0..9).all(|b| {
+            let (bx, by) = (b / 3 * 3 + x % 3, b % 3 * 3 + y % 3);
+            (bx, by) == (x, y) || board.unchecked_get(bx, by) != Some(val)
+        })
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some("variant: disjoint".to_string())
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+/// four extra 3x3 windows which can not hold the same value twice.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Windoku;
+
+impl Windoku {
+    /// the top left slot of the window which contain a slot.
+    fn window(x: usize, y: usize) -> Option<Pos> {
+        let start = |i: usize| match i {
+            1..=3 => Some(1),
+            5..=7 => Some(5),
+            _ => None,
+        };
+        Some((start(x)?, start(y)?))
+    }
+}
+
+impl Constraint for Windoku {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        let (wx, wy) = match Windoku::window(x, y) {
+            Some(window) => window,
+            None => return true,
+        };
+        (0..9).all(|i| {
+            let (nx, ny) = (wx + i / 3, wy + i % 3);
+            (nx, ny) == (x, y) || board.unchecked_get(nx, ny) != Some(val)
+        })
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some("variant: windoku".to_string())
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+/// slots which must hold an even, or an odd, value.
+#[derive(Clone, Debug)]
+pub struct Parity {
+    slots: Vec<Pos>,
+    even: bool,
+}
+
+impl Parity {
+    /// slots which must hold an even value, slots must be on board.
+    pub fn even(slots: Vec<Pos>) -> Result<Self, SuDoKuError> {
+        check_slots(&slots)?;
+        Ok(Parity { slots, even: true })
+    }
+
+    /// slots which must hold an odd value, slots must be on board.
+    pub fn odd(slots: Vec<Pos>) -> Result<Self, SuDoKuError> {
+        check_slots(&slots)?;
+        Ok(Parity { slots, even: false })
+    }
+}
+
+impl Constraint for Parity {
+    fn allows(&self, _board: &Board, x: usize, y: usize, val: u8) -> bool {
+        !self.slots.contains(&(x, y)) || (val & 1 == 0) == self.even
+    }
+
+    fn directive(&self) -> Option<String> {
+        let key = if self.even { "even" } else { "odd" };
+        let slots: Vec<String> = self.slots.iter().map(|&slot| fmt_slot(slot)).collect();
+        Some(format!("{}: {}", key, slots.join(" ")))
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+/// greater-than signs, the first slot of a pair holds the greater value.
+/// ```
+/// use sudoku_rs::{board::Board, constraint::GreaterThan};
+///
+/// let mut b = Board::new();
+/// b.add_constraint(GreaterThan::new(vec![((0, 0), (0, 1))]).unwrap());
+/// assert!(!b.avaliable_val(0, 0).contains(&1));
+/// assert!(!b.avaliable_val(0, 1).contains(&9));
+/// ```
+#[derive(Clone, Debug)]
+pub struct GreaterThan {
+    pairs: Vec<(Pos, Pos)>,
+}
+
+impl GreaterThan {
+    /// return a new set of greater-than signs.
+    /// slots must be on board, and the two slots of a pair distinct.
+    pub fn new(pairs: Vec<(Pos, Pos)>) -> Result<Self, SuDoKuError> {
+        for &(great, less) in pairs.iter() {
+            check_slots(&[great, less])?;
+            if great == less {
+                return Err(SuDoKuError::InvalidFormat);
+            }
+        }
+        Ok(GreaterThan { pairs })
+    }
+}
+
+impl Constraint for GreaterThan {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        self.pairs.iter().all(|&(great, less)| {
+            if great == (x, y) {
+                val > 1
+                    && board
+                        .unchecked_get(less.0, less.1)
+                        .map_or(true, |other| val > other)
+            } else if less == (x, y) {
+                val < 9
+                    && board
+                        .unchecked_get(great.0, great.1)
+                        .map_or(true, |other| val < other)
+            } else {
+                true
+            }
+        })
+    }
+
+    fn directive(&self) -> Option<String> {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|&(great, less)| format!("{}>{}", fmt_slot(great), fmt_slot(less)))
+            .collect();
+        Some(format!("greater: {}", pairs.join(" ")))
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+impl core::str::FromStr for GreaterThan {
+    type Err = SuDoKuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pairs = s
+            .split_whitespace()
+            .map(|word| {
+                let (great, less) = word.split_once('>').ok_or(SuDoKuError::InvalidFormat)?;
+                Ok((parse_slot(great)?, parse_slot(less)?))
+            })
+            .collect::<Result<_, SuDoKuError>>()?;
+        GreaterThan::new(pairs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// a solved board with cells (0, 0), (0, 1), (6, 0) and (6, 1) cleared.
+    /// 9 5 / 5 9 and 5 9 / 9 5 both fit, so it has two solves.
+    pub(super) fn deadly() -> Board {
+        let mut board = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        for &(x, y) in [(0, 0), (0, 1), (6, 0), (6, 1)].iter() {
+            board.unset(x, y);
+        }
+        board
+    }
+
+    #[test]
+    fn anti_knight() {
+        let mut board = Board::new();
+        board.add_constraint(AntiKnight);
+        board.set(4, 4, 5).unwrap();
+        assert!(!board.avaliable_val(2, 3).contains(&5));
+        assert!(!board.avaliable_val(6, 5).contains(&5));
+        assert!(board.avaliable_val(7, 7).contains(&5));
+    }
+
+    #[test]
+    fn anti_king() {
+        let mut board = Board::new();
+        board.add_constraint(AntiKing);
+        board.set(2, 2, 5).unwrap();
+        assert!(!board.avaliable_val(3, 3).contains(&5));
+        assert!(board.avaliable_val(4, 4).contains(&5));
+    }
+
+    #[test]
+    fn non_consecutive() {
+        let mut board = Board::new();
+        board.add_constraint(NonConsecutive);
+        board.set(0, 0, 5).unwrap();
+        assert_eq!(board.avaliable_val(0, 1), vec![1, 2, 3, 7, 8, 9]);
+        board.set(8, 8, 9).unwrap();
+        assert_eq!(board.avaliable_val(8, 7), vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn disjoint() {
+        let mut board = Board::new();
+        board.add_constraint(DisjointGroups);
+        board.set(0, 0, 5).unwrap();
+        assert!(!board.avaliable_val(6, 3).contains(&5));
+        assert!(board.avaliable_val(6, 4).contains(&5));
+    }
+
+    #[test]
+    fn windoku() {
+        let mut board = Board::new();
+        board.add_constraint(Windoku);
+        board.set(1, 1, 5).unwrap();
+        assert!(!board.avaliable_val(3, 3).contains(&5));
+        assert!(board.avaliable_val(4, 4).contains(&5));
+        assert!(board.avaliable_val(3, 4).contains(&5));
+    }
+
+    #[test]
+    fn parity() {
+        let mut board = Board::new();
+        board.add_constraint(Parity::even(vec![(0, 0)]).unwrap());
+        board.add_constraint(Parity::odd(vec![(0, 1)]).unwrap());
+        assert_eq!(board.avaliable_val(0, 0), vec![2, 4, 6, 8]);
+        assert_eq!(board.avaliable_val(0, 1), vec![1, 3, 5, 7, 9]);
+        assert!(matches!(
+            Parity::even(vec![(30, 0)]),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        assert!(matches!(
+            Parity::odd(vec![(0, 0), (0, 9)]),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        assert!("odd: r1c1 r1c10".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn greater_than() {
+        let mut board = Board::new();
+        board.add_constraint(GreaterThan::new(vec![((0, 0), (0, 1))]).unwrap());
+        board.set(0, 1, 6).unwrap();
+        assert_eq!(board.avaliable_val(0, 0), vec![7, 8, 9]);
+        // the own value is ignored
+        board.set(0, 0, 8).unwrap();
+        assert_eq!(board.avaliable_val(0, 1), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert!(matches!(
+            GreaterThan::new(vec![((0, 0), (0, 20))]),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        assert!(matches!(
+            GreaterThan::new(vec![((0, 0), (0, 1)), ((2, 2), (2, 2))]),
+            Err(SuDoKuError::InvalidFormat)
+        ));
+        assert!("greater: r1c1>r1c1".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn check() {
+        let mut board = Board::new();
+        board.set(0, 0, 1).unwrap();
+        board.set(1, 2, 1).unwrap();
+        assert!(!AntiKnight.check(&board));
+        assert!(AntiKing.check(&board));
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "variant: antiknight\nvariant: antiking\nvariant: nonconsecutive\nvariant: disjoint\nvariant: windoku\neven: r1c1 r2c2\nodd: r9c9\ngreater: r1c1>r1c2 r5c5>r6c5\n";
+        let board: Board = text.parse().unwrap();
+        assert_eq!(board.constraints().len(), 8);
+        assert!(board.to_string().ends_with(text));
+    }
+
+    #[test]
+    fn win_check() {
+        let board = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        assert!(board.is_win());
+        let mut knight = board.clone();
+        knight.add_constraint(AntiKnight);
+        assert!(!AntiKnight.check(&board));
+        assert!(!knight.is_win());
+    }
+
+    #[test]
+    fn solve() {
+        use crate::solver::DfsSolver;
+        let mut board = Board::new();
+        board.add_constraint(AntiKing);
+        board.add_constraint(GreaterThan::new(vec![((0, 0), (0, 1))]).unwrap());
+        DfsSolver::new().solve(&mut board).unwrap();
+        assert!(board.is_win());
+        assert!(board.get(0, 0).unwrap() > board.get(0, 1).unwrap());
+    }
+}
@@ -0,0 +1,296 @@
+//! marks between neighbouring slots: kropki dots and XV clues.
+use super::{fmt_slot, Constraint, Pos, ORTHOGONAL};
+use crate::{board::Board, cage::parse_slot, error::SuDoKuError, prelude::*};
+use core::fmt;
+
+/// a kind of mark between two slots.
+pub trait Mark: Copy + PartialEq + fmt::Debug + Send + Sync + 'static {
+    /// every kind of this mark.
+    const ALL: &'static [Self];
+    /// the key in the text format.
+    const KEY: &'static str;
+
+    /// check two values fit the mark.
+    fn fits(self, a: u8, b: u8) -> bool;
+
+    /// the symbol in the text format.
+    fn symbol(self) -> char;
+}
+
+/// kropki dots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kropki {
+    /// the values are consecutive.
+    White,
+    /// one value is double the other.
+    Black,
+}
+
+impl Mark for Kropki {
+    const ALL: &'static [Self] = &[Kropki::White, Kropki::Black];
+    const KEY: &'static str = "kropki";
+
+    fn fits(self, a: u8, b: u8) -> bool {
+        match self {
+            Kropki::White => a + 1 == b || b + 1 == a,
+            Kropki::Black => a * 2 == b || b * 2 == a,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            Kropki::White => 'w',
+            Kropki::Black => 'b',
+        }
+    }
+}
+
+/// XV clues.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XV {
+    /// the values sum up to 10.
+    X,
+    /// the values sum up to 5.
+    V,
+}
+
+impl Mark for XV {
+    const ALL: &'static [Self] = &[XV::X, XV::V];
+    const KEY: &'static str = "xv";
+
+    fn fits(self, a: u8, b: u8) -> bool {
+        match self {
+            XV::X => a + b == 10,
+            XV::V => a + b == 5,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            XV::X => 'x',
+            XV::V => 'v',
+        }
+    }
+}
+
+/// marks between neighbouring slots.
+/// with the negative constraint, neighbours without a mark fit none of the marks.
+/// ```
+/// use sudoku_rs::{board::Board, constraint::{Adjacent, Kropki}};
+///
+/// let mut b = Board::new();
+/// b.add_constraint(Adjacent::new().mark((0, 0), (0, 1), Kropki::Black).unwrap().negative());
+/// b.set(0, 0, 3).unwrap();
+/// assert_eq!(b.avaliable_val(0, 1), vec![6]);
+/// // 3 and 4 are consecutive, 3 and 6 is double
+/// assert_eq!(b.avaliable_val(1, 0), vec![1, 5, 7, 8, 9]);
+/// ```
+/// in the text format a mark is two slots and its symbol.
+/// ```
+/// use sudoku_rs::board::Board;
+///
+/// let b: Board = "1\nxv: r1c1-r1c2=x r2c1-r2c2=v negative".parse().unwrap();
+/// assert_eq!(b.avaliable_val(0, 1), vec![9]);
+/// assert_eq!(b.avaliable_val(1, 1), vec![2, 3, 4]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Adjacent<M> {
+    marks: Vec<(Pos, Pos, M)>,
+    negative: bool,
+}
+
+impl<M: Mark> Default for Adjacent<M> {
+    fn default() -> Self {
+        Adjacent {
+            marks: Vec::new(),
+            negative: false,
+        }
+    }
+}
+
+impl<M: Mark> Adjacent<M> {
+    /// return a new constraint without marks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a mark between two slots, they must be orthogonal neighbours on board.
+    pub fn mark(mut self, a: Pos, b: Pos, mark: M) -> Result<Self, SuDoKuError> {
+        if a.0 >= 9 || a.1 >= 9 || b.0 >= 9 || b.1 >= 9 {
+            return Err(SuDoKuError::OutOfBound);
+        }
+        // only orthogonal neighbours can be marked
+        if a.0.abs_diff(b.0) + a.1.abs_diff(b.1) != 1 {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        self.marks.push((a, b, mark));
+        Ok(self)
+    }
+
+    /// neighbours without a mark fit none of the marks.
+    pub fn negative(mut self) -> Self {
+        self.negative = true;
+        self
+    }
+
+    /// the mark between two slots.
+    fn mark_of(&self, a: Pos, b: Pos) -> Option<M> {
+        self.marks
+            .iter()
+            .find(|&&(p, q, _)| (p, q) == (a, b) || (q, p) == (a, b))
+            .map(|&(_, _, mark)| mark)
+    }
+}
+
+impl<M: Mark> Constraint for Adjacent<M> {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        let marked = self.marks.iter().all(|&(a, b, mark)| {
+            let other = if a == (x, y) {
+                b
+            } else if b == (x, y) {
+                a
+            } else {
+                return true;
+            };
+            match board.unchecked_get(other.0, other.1) {
+                Some(other) => mark.fits(val, other),
+                None => (1..10).any(|other| mark.fits(val, other)),
+            }
+        });
+        if !marked || !self.negative {
+            return marked;
+        }
+        ORTHOGONAL.iter().all(|&(dx, dy)| {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if !(0..9).contains(&nx) || !(0..9).contains(&ny) {
+                return true;
+            }
+            let other = (nx as usize, ny as usize);
+            self.mark_of((x, y), other).is_some()
+                || board.unchecked_get(other.0, other.1).map_or(true, |other| {
+                    M::ALL.iter().all(|mark| !mark.fits(val, other))
+                })
+        })
+    }
+
+    fn directive(&self) -> Option<String> {
+        let mut words: Vec<String> = self
+            .marks
+            .iter()
+            .map(|&(a, b, mark)| format!("{}-{}={}", fmt_slot(a), fmt_slot(b), mark.symbol()))
+            .collect();
+        if self.negative {
+            words.push("negative".to_string());
+        }
+        Some(format!("{}: {}", M::KEY, words.join(" ")))
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+impl<M: Mark> core::str::FromStr for Adjacent<M> {
+    type Err = SuDoKuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut adjacent = Adjacent::new();
+        for word in s.split_whitespace() {
+            if word == "negative" {
+                adjacent.negative = true;
+                continue;
+            }
+            let (pair, symbol) = word.split_once('=').ok_or(SuDoKuError::InvalidFormat)?;
+            let (a, b) = pair.split_once('-').ok_or(SuDoKuError::InvalidFormat)?;
+            let (a, b) = (parse_slot(a)?, parse_slot(b)?);
+            let mark = M::ALL
+                .iter()
+                .find(|mark| symbol.len() == 1 && symbol.starts_with(mark.symbol()))
+                .ok_or(SuDoKuError::InvalidFormat)?;
+            adjacent = adjacent.mark(a, b, *mark)?;
+        }
+        Ok(adjacent)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{constraint::test::deadly, solver::DfsSolver};
+
+    #[test]
+    fn kropki() {
+        let mut board = Board::new();
+        let kropki = Adjacent::new()
+            .mark((4, 4), (4, 5), Kropki::White)
+            .and_then(|kropki| kropki.mark((4, 4), (5, 4), Kropki::Black))
+            .unwrap();
+        board.add_constraint(kropki);
+        board.set(4, 4, 4).unwrap();
+        assert_eq!(board.avaliable_val(4, 5), vec![3, 5]);
+        assert_eq!(board.avaliable_val(5, 4), vec![2, 8]);
+        // no negative constraint
+        assert!(board.avaliable_val(3, 4).contains(&5));
+
+        let mut board = deadly();
+        // (6, 0) is 5 or 9, (7, 0) is 4
+        board.add_constraint(Adjacent::new().mark((6, 0), (7, 0), Kropki::White).unwrap());
+        assert!(DfsSolver::new().unique(&mut board).unwrap());
+    }
+
+    #[test]
+    fn xv() {
+        let mut board = Board::new();
+        board.add_constraint(
+            Adjacent::new()
+                .mark((0, 0), (1, 0), XV::V)
+                .unwrap()
+                .negative(),
+        );
+        assert_eq!(board.avaliable_val(0, 0), vec![1, 2, 3, 4]);
+        board.set(0, 0, 3).unwrap();
+        assert_eq!(board.avaliable_val(1, 0), vec![2]);
+        // 2 sums up to 5 and 7 sums up to 10
+        assert_eq!(board.avaliable_val(0, 1), vec![1, 4, 5, 6, 8, 9]);
+
+        let mut board = deadly();
+        // (6, 1) is 5 or 9, (7, 1) is 1
+        board.add_constraint(Adjacent::new().mark((6, 1), (7, 1), XV::X).unwrap());
+        assert!(DfsSolver::new().unique(&mut board).unwrap());
+    }
+
+    #[test]
+    fn negative() {
+        let negative = Adjacent::<Kropki>::new().negative();
+        let mut board = Board::new();
+        board.set(0, 0, 1).unwrap();
+        board.set(0, 1, 5).unwrap();
+        assert!(negative.check(&board));
+        board.set(1, 0, 2).unwrap();
+        assert!(!negative.check(&board));
+        assert!(negative
+            .clone()
+            .mark((0, 0), (1, 0), Kropki::Black)
+            .unwrap()
+            .check(&board));
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "kropki: r1c1-r1c2=w r2c1-r3c1=b negative\nxv: r5c5-r5c6=v\n";
+        let board: Board = text.parse().unwrap();
+        assert_eq!(board.constraints().len(), 2);
+        assert!(board.to_string().ends_with(text));
+        assert!("kropki: r1c1-r1c3=w".parse::<Board>().is_err());
+        assert!("xv: r1c1-r1c2=w".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn neighbours_only() {
+        assert!(Adjacent::new().mark((0, 0), (1, 1), XV::X).is_err());
+        assert!(Adjacent::new().mark((0, 0), (0, 2), XV::X).is_err());
+        assert!(Adjacent::new().mark((0, 0), (0, 0), XV::X).is_err());
+        assert!(Adjacent::new().mark((8, 8), (9, 8), XV::X).is_err());
+        assert!(Adjacent::new().mark((8, 8), (7, 8), XV::X).is_ok());
+    }
+}
@@ -0,0 +1,452 @@
+//! line and arrow clues: thermometers, arrows, sandwich sums and little killers.
+use super::{check_slots, fmt_slot, parse_slots, Constraint, Pos};
+use crate::{board::Board, cage::parse_slot, error::SuDoKuError, prelude::*};
+
+/// the placed values of some slots, the slot (x, y) takes `val` instead.
+fn values(board: &Board, slots: &[Pos], x: usize, y: usize, val: u8) -> Vec<Option<u8>> {
+    slots
+        .iter()
+        .map(|&slot| {
+            if slot == (x, y) {
+                Some(val)
+            } else {
+                board.unchecked_get(slot.0, slot.1)
+            }
+        })
+        .collect()
+}
+
+/// check some values can still sum up to `sum`, every empty one is in 1..=9.
+fn reachable(values: &[Option<u8>], sum: usize) -> bool {
+    let placed: usize = values.iter().flatten().map(|&v| v as usize).sum();
+    let empty = values.iter().filter(|v| v.is_none()).count();
+    placed + empty <= sum && sum <= placed + 9 * empty
+}
+
+/// values strictly increase from the bulb along the line.
+/// ```
+/// use sudoku_rs::{board::Board, constraint::Thermometer};
+///
+/// let mut b = Board::new();
+/// b.add_constraint(Thermometer::new(vec![(0, 0), (0, 1), (0, 2)]).unwrap());
+/// assert_eq!(b.avaliable_val(0, 1), vec![2, 3, 4, 5, 6, 7, 8]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Thermometer {
+    slots: Vec<Pos>,
+}
+
+impl Thermometer {
+    /// return a new thermometer, the first slot is the bulb.
+    /// slots must be on board.
+    pub fn new(slots: Vec<Pos>) -> Result<Self, SuDoKuError> {
+        check_slots(&slots)?;
+        Ok(Thermometer { slots })
+    }
+}
+
+impl Constraint for Thermometer {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        let i = match self.slots.iter().position(|&slot| slot == (x, y)) {
+            Some(i) => i,
+            None => return true,
+        };
+        let len = self.slots.len();
+        if (val as usize) < i + 1 || val as usize + (len - 1 - i) > 9 {
+            return false;
+        }
+        self.slots
+            .iter()
+            .enumerate()
+            .all(|(j, &(sx, sy))| match board.unchecked_get(sx, sy) {
+                Some(other) if j < i => other as usize + (i - j) <= val as usize,
+                Some(other) if j > i => val as usize + (j - i) <= other as usize,
+                _ => true,
+            })
+    }
+
+    fn directive(&self) -> Option<String> {
+        let slots: Vec<String> = self.slots.iter().map(|&slot| fmt_slot(slot)).collect();
+        Some(format!("thermo: {}", slots.join(" ")))
+    }
+
+    fn pairwise(&self) -> bool {
+        true
+    }
+}
+
+/// the value in the circle equals the sum of the values along the arrow.
+/// ```
+/// use sudoku_rs::{board::Board, constraint::Arrow};
+///
+/// let mut b = Board::new();
+/// b.add_constraint(Arrow::new((0, 0), vec![(1, 1), (2, 2)]).unwrap());
+/// b.set(1, 1, 4).unwrap();
+/// assert_eq!(b.avaliable_val(0, 0), vec![5, 6, 7, 8, 9]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Arrow {
+    circle: Pos,
+    slots: Vec<Pos>,
+}
+
+impl Arrow {
+    /// return a new arrow, the circle and the slots must be on board.
+    pub fn new(circle: Pos, slots: Vec<Pos>) -> Result<Self, SuDoKuError> {
+        check_slots(&[circle])?;
+        check_slots(&slots)?;
+        Ok(Arrow { circle, slots })
+    }
+}
+
+impl Constraint for Arrow {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        if self.circle != (x, y) && !self.slots.contains(&(x, y)) {
+            return true;
+        }
+        let arrow = values(board, &self.slots, x, y, val);
+        let circle = if self.circle == (x, y) {
+            Some(val)
+        } else {
+            board.unchecked_get(self.circle.0, self.circle.1)
+        };
+        match circle {
+            Some(circle) => reachable(&arrow, circle as usize),
+            // the circle is at most 9
+            None => (1..10).any(|circle| reachable(&arrow, circle)),
+        }
+    }
+
+    fn directive(&self) -> Option<String> {
+        let slots: Vec<String> = core::iter::once(self.circle)
+            .chain(self.slots.iter().cloned())
+            .map(fmt_slot)
+            .collect();
+        Some(format!("arrow: {}", slots.join(" ")))
+    }
+}
+
+/// the sum of the values between 1 and 9 in a row or column.
+/// ```
+/// use sudoku_rs::{board::Board, constraint::Sandwich};
+///
+/// let mut b = Board::new();
+/// b.add_constraint(Sandwich::row(0, 0).unwrap());
+/// b.set(0, 4, 1).unwrap();
+/// assert_eq!(b.avaliable_val(0, 3), vec![2, 3, 4, 5, 6, 7, 8, 9]);
+/// b.set(0, 3, 9).unwrap();
+/// assert_eq!(b.avaliable_val(0, 2), vec![2, 3, 4, 5, 6, 7, 8]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Sandwich {
+    /// true for a row, false for a column.
+    row: bool,
+    line: usize,
+    sum: usize,
+}
+
+impl Sandwich {
+    /// a sandwich sum of row `x`, the sum is at most 2 + 3 + ... + 8.
+    pub fn row(x: usize, sum: usize) -> Result<Self, SuDoKuError> {
+        Sandwich::new(true, x, sum)
+    }
+
+    /// a sandwich sum of column `y`, the sum is at most 2 + 3 + ... + 8.
+    pub fn column(y: usize, sum: usize) -> Result<Self, SuDoKuError> {
+        Sandwich::new(false, y, sum)
+    }
+
+    fn new(row: bool, line: usize, sum: usize) -> Result<Self, SuDoKuError> {
+        if line >= 9 {
+            return Err(SuDoKuError::OutOfBound);
+        }
+        if sum > 35 {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        Ok(Sandwich { row, line, sum })
+    }
+
+    fn slots(&self) -> Vec<Pos> {
+        (0..9)
+            .map(|i| {
+                if self.row {
+                    (self.line, i)
+                } else {
+                    (i, self.line)
+                }
+            })
+            .collect()
+    }
+}
+
+impl Constraint for Sandwich {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        let slots = self.slots();
+        if !slots.contains(&(x, y)) {
+            return true;
+        }
+        let line = values(board, &slots, x, y, val);
+        let one = line.iter().position(|&v| v == Some(1));
+        let nine = line.iter().position(|&v| v == Some(9));
+        match (one, nine) {
+            (Some(one), Some(nine)) => {
+                let between = &line[one.min(nine) + 1..one.max(nine)];
+                // the values between are neither 1 nor 9
+                let empty = between.iter().filter(|v| v.is_none()).count();
+                let placed: usize = between.iter().flatten().map(|&v| v as usize).sum();
+                placed + 2 * empty <= self.sum && self.sum <= placed + 8 * empty
+            }
+            _ => true,
+        }
+    }
+
+    fn directive(&self) -> Option<String> {
+        let line = if self.row { 'r' } else { 'c' };
+        Some(format!("sandwich: {}{} {}", line, self.line + 1, self.sum))
+    }
+}
+
+impl core::str::FromStr for Sandwich {
+    type Err = SuDoKuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (line, sum) = s.trim().split_once(' ').ok_or(SuDoKuError::InvalidFormat)?;
+        let sum = sum.trim().parse().map_err(|_| SuDoKuError::InvalidFormat)?;
+        let index = |n: &str| match n.parse::<usize>() {
+            Ok(n @ 1..=9) => Ok(n - 1),
+            _ => Err(SuDoKuError::InvalidFormat),
+        };
+        if let Some(x) = line.strip_prefix('r') {
+            Sandwich::row(index(x)?, sum)
+        } else if let Some(y) = line.strip_prefix('c') {
+            Sandwich::column(index(y)?, sum)
+        } else {
+            Err(SuDoKuError::InvalidFormat)
+        }
+    }
+}
+
+/// the sum of a diagonal, from a slot on the edge to the other edge.
+/// values can repeat on the diagonal.
+/// ```
+/// use sudoku_rs::{board::Board, constraint::LittleKiller};
+///
+/// let mut b = Board::new();
+/// // r1c8, r2c9
+/// b.add_constraint(LittleKiller::new(3, (0, 7), (1, 1)).unwrap());
+/// assert_eq!(b.avaliable_val(1, 8), vec![1, 2]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LittleKiller {
+    sum: usize,
+    slots: Vec<Pos>,
+    dir: (isize, isize),
+}
+
+impl LittleKiller {
+    /// a diagonal from `start` going `dir` step by step, `dir` is like `(1, -1)`.
+    /// `start` must be on board and the sum reachable by the diagonal.
+    pub fn new(sum: usize, start: Pos, dir: (isize, isize)) -> Result<Self, SuDoKuError> {
+        check_slots(&[start])?;
+        if dir.0.abs() != 1 || dir.1.abs() != 1 {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        let mut slots = Vec::new();
+        let (mut x, mut y) = (start.0 as isize, start.1 as isize);
+        while (0..9).contains(&x) && (0..9).contains(&y) {
+            slots.push((x as usize, y as usize));
+            x += dir.0;
+            y += dir.1;
+        }
+        if !reachable(&vec![None; slots.len()], sum) {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        Ok(LittleKiller { sum, slots, dir })
+    }
+}
+
+impl Constraint for LittleKiller {
+    fn allows(&self, board: &Board, x: usize, y: usize, val: u8) -> bool {
+        !self.slots.contains(&(x, y)) || reachable(&values(board, &self.slots, x, y, val), self.sum)
+    }
+
+    fn directive(&self) -> Option<String> {
+        let dir = match self.dir {
+            (-1, -1) => "nw",
+            (-1, 1) => "ne",
+            (1, -1) => "sw",
+            _ => "se",
+        };
+        let start = self.slots.first().cloned().unwrap_or((0, 0));
+        Some(format!(
+            "littlekiller: {} {} {}",
+            self.sum,
+            fmt_slot(start),
+            dir
+        ))
+    }
+}
+
+impl core::str::FromStr for LittleKiller {
+    type Err = SuDoKuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        if words.len() != 3 {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        let sum = words[0].parse().map_err(|_| SuDoKuError::InvalidFormat)?;
+        let start = parse_slot(words[1])?;
+        let dir = match words[2] {
+            "nw" => (-1, -1),
+            "ne" => (-1, 1),
+            "sw" => (1, -1),
+            "se" => (1, 1),
+            _ => return Err(SuDoKuError::InvalidFormat),
+        };
+        LittleKiller::new(sum, start, dir)
+    }
+}
+
+/// parse the slots of an arrow, the circle first.
+pub(super) fn parse_arrow(value: &str) -> Result<Arrow, SuDoKuError> {
+    let mut slots = parse_slots(value)?;
+    if slots.len() < 2 {
+        return Err(SuDoKuError::InvalidFormat);
+    }
+    let circle = slots.remove(0);
+    Arrow::new(circle, slots)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        constraint::{test::deadly, GreaterThan},
+        solver::DfsSolver,
+    };
+
+    #[test]
+    fn deadly_pattern() {
+        assert!(!DfsSolver::new().unique(&mut deadly()).unwrap());
+        let mut board = deadly();
+        board.add_constraint(GreaterThan::new(vec![((0, 0), (0, 1))]).unwrap());
+        assert!(DfsSolver::new().unique(&mut board).unwrap());
+    }
+
+    #[test]
+    fn thermometer() {
+        let mut board = Board::new();
+        board.add_constraint(Thermometer::new(vec![(4, 0), (4, 1), (4, 2), (3, 2)]).unwrap());
+        assert_eq!(board.avaliable_val(4, 0), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(board.avaliable_val(3, 2), vec![4, 5, 6, 7, 8, 9]);
+        board.set(4, 1, 5).unwrap();
+        assert_eq!(board.avaliable_val(4, 2), vec![6, 7, 8]);
+        assert_eq!(board.avaliable_val(4, 0), vec![1, 2, 3, 4]);
+
+        let mut board = deadly();
+        board.add_constraint(Thermometer::new(vec![(0, 1), (0, 0)]).unwrap());
+        let solver = DfsSolver::new();
+        assert!(solver.unique(&mut board.clone()).unwrap());
+        solver.solve(&mut board).unwrap();
+        assert_eq!(board.get(0, 0).unwrap(), Some(9));
+    }
+
+    #[test]
+    fn arrow() {
+        let mut board = Board::new();
+        board.add_constraint(Arrow::new((4, 4), vec![(3, 3), (2, 2), (1, 1)]).unwrap());
+        assert_eq!(board.avaliable_val(4, 4), vec![3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(board.avaliable_val(3, 3), vec![1, 2, 3, 4, 5, 6, 7]);
+        board.set(4, 4, 5).unwrap();
+        board.set(3, 3, 2).unwrap();
+        assert_eq!(board.avaliable_val(2, 2), vec![1, 2]);
+
+        let mut board = deadly();
+        board.add_constraint(Arrow::new((0, 0), vec![(0, 1), (1, 1)]).unwrap());
+        assert!(DfsSolver::new().unique(&mut board).unwrap());
+    }
+
+    #[test]
+    fn sandwich() {
+        let mut board = Board::new();
+        board.add_constraint(Sandwich::column(0, 5).unwrap());
+        board.set(0, 0, 1).unwrap();
+        board.set(3, 0, 9).unwrap();
+        // two slots between, 2 + 3 only
+        assert_eq!(board.avaliable_val(1, 0), vec![2, 3]);
+
+        let mut board = deadly();
+        // 1 at (2, 2), 9 at (0, 0) or (0, 1)
+        board.add_constraint(Sandwich::row(0, 5 + 3).unwrap());
+        assert!(DfsSolver::new().unique(&mut board.clone()).unwrap());
+        let mut board = deadly();
+        board.add_constraint(Sandwich::row(0, 3).unwrap());
+        assert!(DfsSolver::new().unique(&mut board.clone()).unwrap());
+    }
+
+    #[test]
+    fn little_killer() {
+        let killer = LittleKiller::new(10, (8, 0), (-1, 1)).unwrap();
+        assert_eq!(killer.slots.len(), 9);
+        let mut board = Board::new();
+        board.add_constraint(LittleKiller::new(4, (2, 0), (-1, 1)).unwrap());
+        board.set(2, 0, 1).unwrap();
+        // 1 is taken by the box
+        assert_eq!(board.avaliable_val(1, 1), vec![2]);
+
+        let mut board = deadly();
+        // (6, 0) is 5 or 9, (7, 1) is 1 and (8, 2) is 7
+        board.add_constraint(LittleKiller::new(17, (6, 0), (1, 1)).unwrap());
+        assert!(DfsSolver::new().unique(&mut board).unwrap());
+    }
+
+    #[test]
+    fn out_of_bound() {
+        assert!(matches!(
+            Thermometer::new(vec![(0, 0), (9, 9)]),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        assert!(matches!(
+            Arrow::new((0, 0), vec![(12, 1)]),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        assert!(matches!(
+            Arrow::new((9, 0), vec![(1, 1)]),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        assert!(matches!(Sandwich::row(10, 5), Err(SuDoKuError::OutOfBound)));
+        assert!(matches!(
+            Sandwich::column(9, 5),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        assert!(matches!(
+            Sandwich::row(0, 36),
+            Err(SuDoKuError::InvalidFormat)
+        ));
+        assert!(matches!(
+            LittleKiller::new(5, (9, 0), (-1, 1)),
+            Err(SuDoKuError::OutOfBound)
+        ));
+        for &dir in [(5, 5), (0, 0), (1, 0), (0, -1)].iter() {
+            assert!(matches!(
+                LittleKiller::new(5, (0, 0), dir),
+                Err(SuDoKuError::InvalidFormat)
+            ));
+        }
+        // r1c8 and r2c9 sum up to 2..=18
+        assert!(LittleKiller::new(0, (0, 7), (1, 1)).is_err());
+        assert!(LittleKiller::new(19, (0, 7), (1, 1)).is_err());
+        assert!(LittleKiller::new(18, (0, 7), (1, 1)).is_ok());
+        assert!("thermo: r1c1 r10c1".parse::<Board>().is_err());
+        assert!("littlekiller: 0 r1c8 se".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "thermo: r1c1 r1c2\narrow: r5c5 r4c4 r3c3\nsandwich: r1 8\nsandwich: c9 0\nlittlekiller: 17 r7c1 se\n";
+        let board: Board = text.parse().unwrap();
+        assert_eq!(board.constraints().len(), 5);
+        assert!(board.to_string().ends_with(text));
+    }
+}
@@ -0,0 +1,698 @@
+//! walk and count the completed grids of a classic sudoku.
+//!
+//! `DfsSolver::solutions` walks the grids of any board, `Band::grids` the grids under a band.
+//! the first band, rows 0 to 2, is walked on its own by `bands`, with the first box fixed to
+//! `1..=9`. every grid is one of these bands relabeled by one of the `9!` orders of values.
+//!
+//! the grids are counted band by band. the other bands only meet the first in its columns,
+//! so bands with the same values in every column have as many grids under them. so do bands
+//! which turn into each other by swapping columns in a stack, stacks or relabeling.
+//! one band of each class is counted.
+//! ```
+//! use sudoku_rs::enumerate::Band;
+//!
+//! let band = Band::new([
+//!     1, 2, 3, 4, 5, 6, 7, 8, 9, //
+//!     4, 5, 6, 7, 8, 9, 1, 2, 3, //
+//!     7, 8, 9, 1, 2, 3, 4, 5, 6, //
+//! ])
+//! .unwrap();
+//! let first = band.grids().next().unwrap().unwrap();
+//! assert!(first.is_win());
+//! assert_eq!(Band::from_board(&first).unwrap(), band);
+//! // the last two boxes swapped
+//! let swapped = Band::new([
+//!     1, 2, 3, 7, 8, 9, 4, 5, 6, //
+//!     4, 5, 6, 1, 2, 3, 7, 8, 9, //
+//!     7, 8, 9, 4, 5, 6, 1, 2, 3, //
+//! ])
+//! .unwrap();
+//! assert_eq!(band.class().unwrap(), swapped.class().unwrap());
+//! ```
+use crate::{
+    bitset::CandidateSet,
+    board::Board,
+    error::SuDoKuError,
+    prelude::*,
+    rng::Rng,
+    solver::{DfsSolver, Puzzle, Search, Solutions},
+};
+use alloc::collections::BTreeMap;
+
+/// the values `1..=9`.
+const FULL: u16 = 0b11_1111_1110;
+
+const PERMS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// the most grids under a band, the first band of `band_classes` has them.
+const MAX_COMPLETIONS: u64 = 7_802_998_272;
+
+/// the orders of values, `9!`.
+const LABELINGS: u128 = 362_880;
+
+/// the first three rows of a classic board, 27 values row by row, 0 for empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Band {
+    cells: [u8; 27],
+}
+
+impl Band {
+    /// return a band of 27 values row by row, 0 for empty.
+    /// fail with `SuDoKuError::OutOfBound` for a value over 9
+    /// and `SuDoKuError::DuplicateValue` for a value twice in a row, column or box.
+    pub fn new(cells: [u8; 27]) -> Result<Band, SuDoKuError> {
+        if cells.iter().any(|&val| val > 9) {
+            return Err(SuDoKuError::OutOfBound);
+        }
+        let mut band = Band { cells: [0; 27] };
+        for (pos, &val) in cells.iter().enumerate() {
+            if val != 0 {
+                if !band.candidates(pos / 9, pos % 9).contains(val) {
+                    return Err(SuDoKuError::DuplicateValue);
+                }
+                band.cells[pos] = val;
+            }
+        }
+        Ok(band)
+    }
+
+    /// the first three rows of a board.
+    /// fail with `SuDoKuError::InvalidFormat` if the board is not classic.
+    pub fn from_board(board: &Board) -> Result<Band, SuDoKuError> {
+        if !board.is_classic() {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        let mut cells = [0; 27];
+        for (pos, cell) in cells.iter_mut().enumerate() {
+            *cell = board.unchecked_get(pos / 9, pos % 9).unwrap_or(0);
+        }
+        Band::new(cells)
+    }
+
+    /// the 27 values row by row, 0 for empty.
+    pub fn cells(&self) -> [u8; 27] {
+        self.cells
+    }
+
+    /// the value at row `x` and column `y`.
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        Some(self.cells[x * 9 + y]).filter(|&val| val != 0)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.cells.iter().all(|&val| val != 0)
+    }
+
+    /// a board with the band on top and the other rows empty.
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new();
+        for (pos, &val) in self.cells.iter().enumerate() {
+            if val != 0 {
+                board.unchecked_set(pos / 9, pos % 9, val);
+            }
+        }
+        board
+    }
+
+    /// every grid with this band on top, in the dfs order.
+    pub fn grids(&self) -> Solutions {
+        DfsSolver::new().solutions(&self.to_board())
+    }
+
+    /// the values of every column.
+    pub fn columns(&self) -> [CandidateSet; 9] {
+        let mut cols = [CandidateSet::new(); 9];
+        for (pos, &val) in self.cells.iter().enumerate() {
+            if val != 0 {
+                cols[pos % 9].insert(val);
+            }
+        }
+        cols
+    }
+
+    /// how many grids have this full band on top.
+    /// fail with `SuDoKuError::InvalidFormat` if the band is not full.
+    ///
+    /// the grids are not walked. the columns of a stack in the second band take three of the
+    /// six values they miss each, 56 ways, and the third band takes the rest. for each of the
+    /// `56^3` choices the ways to order both bands are multiplied.
+    pub fn completions(&self) -> Result<u128, SuDoKuError> {
+        Ok(completions(self.masks()?))
+    }
+
+    /// the class of a full band, the smallest values of the columns under the symmetries
+    /// in the module doc. bands of a class have as many grids under them.
+    /// fail with `SuDoKuError::InvalidFormat` if the band is not full.
+    pub fn class(&self) -> Result<[CandidateSet; 9], SuDoKuError> {
+        Ok(class(self.masks()?).map(CandidateSet::from_bits))
+    }
+
+    /// the bits of `columns` of a full band.
+    fn masks(&self) -> Result<[u16; 9], SuDoKuError> {
+        if !self.is_full() {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        Ok(self.columns().map(CandidateSet::bits))
+    }
+}
+
+impl Puzzle for Band {
+    fn empty_slots(&self) -> Vec<(usize, usize)> {
+        (0..27)
+            .filter(|&pos| self.cells[pos] == 0)
+            .map(|pos| (pos / 9, pos % 9))
+            .collect()
+    }
+
+    fn candidates(&self, x: usize, y: usize) -> CandidateSet {
+        let mut used = 0u16;
+        for i in 0..9 {
+            used |= 1 << self.cells[x * 9 + i];
+            used |= 1 << self.cells[i / 3 * 9 + y / 3 * 3 + i % 3];
+        }
+        for i in 0..3 {
+            used |= 1 << self.cells[i * 9 + y];
+        }
+        CandidateSet::from_bits(used).complement()
+    }
+
+    fn avaliable_count(&self, x: usize, y: usize) -> usize {
+        self.candidates(x, y).count()
+    }
+
+    fn place(&mut self, x: usize, y: usize, val: u8) {
+        self.cells[x * 9 + y] = val;
+    }
+
+    fn clear(&mut self, x: usize, y: usize) {
+        self.cells[x * 9 + y] = 0;
+    }
+
+    fn feasible(&self, _x: usize, _y: usize) -> bool {
+        true
+    }
+}
+
+/// the bands with the first box `1..=9`, see `bands`.
+pub struct Bands {
+    search: Search<Band>,
+}
+
+impl Iterator for Bands {
+    type Item = Band;
+
+    fn next(&mut self) -> Option<Band> {
+        // no budget, the search only ends
+        match self.search.step() {
+            Ok(true) => Some(*self.search.puzzle()),
+            _ => None,
+        }
+    }
+}
+
+/// every full band with the first box `1..=9`, 2,612,736 of them, in the dfs order.
+/// ```
+/// use sudoku_rs::enumerate;
+///
+/// let first = enumerate::bands().next().unwrap();
+/// assert!(first.is_full());
+/// assert_eq!(first.cells()[..3], [1, 2, 3]);
+/// ```
+pub fn bands() -> Bands {
+    let mut cells = [0; 27];
+    for pos in 0..9 {
+        cells[pos / 3 * 9 + pos % 3] = pos as u8 + 1;
+    }
+    Bands {
+        search: Search::new(DfsSolver::new(), Band { cells }),
+    }
+}
+
+/// the classes of `bands`, the first band of each and how many bands are in it.
+/// see `Band::class`.
+pub fn band_classes() -> Vec<(Band, u64)> {
+    // many bands have the same columns up to sorting, their class is found once
+    let mut columns: BTreeMap<[u16; 9], (Band, u64)> = BTreeMap::new();
+    for band in bands() {
+        let masks = sorted(band.masks().expect("bands are full"));
+        columns.entry(masks).or_insert((band, 0)).1 += 1;
+    }
+    let mut classes: BTreeMap<[u16; 9], (Band, u64)> = BTreeMap::new();
+    for (masks, (band, size)) in columns {
+        let class = classes.entry(class(masks)).or_insert((band, 0));
+        class.0 = class.0.min(band);
+        class.1 += size;
+    }
+    classes.into_values().collect()
+}
+
+/// how many grids a classic sudoku has, 6,670,903,752,021,072,936,960.
+/// the grids under one band of each class are counted, it takes a while.
+pub fn count_grids() -> u128 {
+    let per_labeling: u128 = band_classes()
+        .iter()
+        .map(|(band, size)| u128::from(*size) * band.completions().expect("bands are full"))
+        .sum();
+    per_labeling * LABELINGS
+}
+
+/// the smallest columns under swapping columns in a stack, stacks and relabeling.
+///
+/// the first stack and the order of values in its columns fix the labels,
+/// the other stacks are then sorted.
+fn class(cols: [u16; 9]) -> [u16; 9] {
+    let vals = cols.map(values);
+    let mut best = [u16::MAX; 9];
+    for first in 0..3 {
+        let rest: Vec<usize> = (0..9).filter(|&y| y / 3 != first).collect();
+        for order in PERMS.iter() {
+            for a in PERMS.iter() {
+                for b in PERMS.iter() {
+                    for c in PERMS.iter() {
+                        let mut map = [0; 10];
+                        for (i, inner) in [a, b, c].iter().enumerate() {
+                            for j in 0..3 {
+                                map[vals[first * 3 + order[i]][inner[j]]] = i * 3 + j + 1;
+                            }
+                        }
+                        let mut relabeled = [0; 9];
+                        for (col, &y) in relabeled[3..].iter_mut().zip(rest.iter()) {
+                            *col = vals[y].iter().fold(0, |col, &val| col | 1 << map[val]);
+                        }
+                        // the first stack is always the same now
+                        relabeled[..3].copy_from_slice(&[0b1110, 0b111_0000, 0b11_1000_0000]);
+                        best = best.min(sorted(relabeled));
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+/// the columns sorted in every stack, the last two stacks sorted.
+fn sorted(cols: [u16; 9]) -> [u16; 9] {
+    let mut stacks = [[0; 3]; 3];
+    for (pos, &col) in cols.iter().enumerate() {
+        stacks[pos / 3][pos % 3] = col;
+    }
+    stacks.iter_mut().for_each(|stack| stack.sort_unstable());
+    stacks[1..].sort_unstable();
+    let mut cols = [0; 9];
+    for (pos, col) in cols.iter_mut().enumerate() {
+        *col = stacks[pos / 3][pos % 3];
+    }
+    cols
+}
+
+/// the grids under a full band with the values `cols` in its columns, see `Band::completions`.
+fn completions(cols: [u16; 9]) -> u128 {
+    let (second, third) = choices(cols);
+    let second = orders(&second);
+    let third = orders(&third);
+    second
+        .iter()
+        .zip(third.iter())
+        .map(|(&a, &b)| u128::from(a) * u128::from(b))
+        .sum()
+}
+
+/// the values the columns of every stack can take in the second band,
+/// and what is left for the third band in the same order.
+fn choices(cols: [u16; 9]) -> ([Vec<Stack>; 3], [Vec<Stack>; 3]) {
+    let mut second: [Vec<Stack>; 3] = Default::default();
+    let mut third: [Vec<Stack>; 3] = Default::default();
+    for stack in 0..3 {
+        let used = [cols[stack * 3], cols[stack * 3 + 1], cols[stack * 3 + 2]];
+        second[stack] = next_stacks(used);
+        third[stack] = second[stack]
+            .iter()
+            .map(|next| {
+                let mut rest = [0; 3];
+                for i in 0..3 {
+                    rest[i] = FULL & !used[i] & !next[i];
+                }
+                rest
+            })
+            .collect();
+    }
+    (second, third)
+}
+
+/// a grid drawn uniformly from all classic grids, the same for the same `rng` state.
+/// about ten times slower than `DfsSolver::solve_random`, which favours some grids.
+/// ```
+/// use sudoku_rs::{enumerate, rng::SplitMix64};
+///
+/// assert!(enumerate::random_grid(&mut SplitMix64::new(2)).is_win());
+/// ```
+///
+/// a band with the first box `1..=9` is drawn uniformly and kept with the chance of its grids
+/// over `MAX_COMPLETIONS`, so bands come as often as grids have them. the values of the other
+/// bands are drawn by how many ways both can be ordered, then an order of each, then labels.
+pub fn random_grid<R: Rng>(rng: &mut R) -> Board {
+    loop {
+        let band = random_band(rng);
+        let (second, third) = choices(band.masks().expect("random bands are full"));
+        let weights: Vec<u64> = orders(&second)
+            .iter()
+            .zip(orders(&third).iter())
+            .map(|(&a, &b)| a * b)
+            .collect();
+        let total: u64 = weights.iter().sum();
+        if rng.below_u64(MAX_COMPLETIONS) >= total {
+            continue;
+        }
+        let mut pick = rng.below_u64(total);
+        let index = weights
+            .iter()
+            .position(|&weight| {
+                if pick < weight {
+                    return true;
+                }
+                pick -= weight;
+                false
+            })
+            .expect("the pick is below the total");
+        let (n1, n2) = (second[1].len(), second[2].len());
+        let choice = [index / (n1 * n2), index / n2 % n1, index % n2];
+        let mut cells = vec![0; 81];
+        cells[..27].copy_from_slice(&band.cells);
+        for (x, stacks) in [(3, &second), (6, &third)] {
+            let cols = [0, 1, 2].map(|stack| stacks[stack][choice[stack]]);
+            let rows = random_order(rng, cols);
+            for (stack, (&key, stack_cols)) in rows.iter().zip(cols.iter()).enumerate() {
+                for (i, shift) in [0, 10, 20].iter().enumerate() {
+                    let row = (key >> shift) as u16 & FULL;
+                    for (j, &col) in stack_cols.iter().enumerate() {
+                        cells[(x + i) * 9 + stack * 3 + j] = (row & col).trailing_zeros() as u8;
+                    }
+                }
+            }
+        }
+        let mut labels: Vec<u8> = (1..=9).collect();
+        rng.shuffle(&mut labels);
+        for cell in cells.iter_mut() {
+            *cell = labels[*cell as usize - 1];
+        }
+        return Board::from_vec(cells);
+    }
+}
+
+/// a band with the first box `1..=9`, drawn uniformly.
+///
+/// the rows of the second box take three values each which are not in the row of the first
+/// box, 56 ways like the columns in `next_stacks`. the third box takes the rest, then the
+/// values in every row of both boxes are shuffled.
+fn random_band<R: Rng>(rng: &mut R) -> Band {
+    let first = [0b1110, 0b111_0000, 0b11_1000_0000];
+    let seconds = next_stacks(first);
+    let second = seconds[rng.below(seconds.len())];
+    let mut cells = [0; 27];
+    for x in 0..3 {
+        let boxes = [first[x], second[x], FULL & !first[x] & !second[x]];
+        for (i, &mask) in boxes.iter().enumerate() {
+            let mut vals = values(mask);
+            if i != 0 {
+                rng.shuffle(&mut vals);
+            }
+            for (j, &val) in vals.iter().enumerate() {
+                cells[x * 9 + i * 3 + j] = val as u8;
+            }
+        }
+    }
+    Band { cells }
+}
+
+/// an order of a band with the values `stacks` in its columns, drawn uniformly.
+fn random_order<R: Rng>(rng: &mut R, stacks: [Stack; 3]) -> [u32; 3] {
+    let [firsts, seconds, mut thirds] = stacks.map(|stack| rows(stack, false));
+    thirds.sort_unstable();
+    let full = u32::from(FULL) * (1 | 1 << 10 | 1 << 20);
+    let mut valid = Vec::new();
+    for &a in firsts.iter() {
+        for &b in seconds.iter() {
+            let c = full & !a & !b;
+            if a & b == 0 && thirds.binary_search(&c).is_ok() {
+                valid.push([a, b, c]);
+            }
+        }
+    }
+    valid[rng.below(valid.len())]
+}
+
+/// the three values in `mask`.
+fn values(mask: u16) -> [usize; 3] {
+    let mut vals = [0; 3];
+    let mut vals_iter = (1..=9).filter(|val| mask & (1 << val) != 0);
+    for val in vals.iter_mut() {
+        *val = vals_iter.next().expect("three values");
+    }
+    vals
+}
+
+/// the values of the three columns of a stack in a band, as bits.
+type Stack = [u16; 3];
+
+/// the sets of three values in `mask`.
+fn triples(mask: u16) -> Vec<u16> {
+    let vals: Vec<u16> = (1..=9).filter(|val| mask & (1 << val) != 0).collect();
+    let mut triples = Vec::new();
+    for (i, a) in vals.iter().enumerate() {
+        for (j, b) in vals.iter().enumerate().skip(i + 1) {
+            for c in vals.iter().skip(j + 1) {
+                triples.push(1 << a | 1 << b | 1 << c);
+            }
+        }
+    }
+    triples
+}
+
+/// the ways the columns of a stack in the next band can take three values each,
+/// none used in the column yet and all nine in the box.
+fn next_stacks(used: Stack) -> Vec<Stack> {
+    let mut stacks = Vec::with_capacity(56);
+    for a in triples(FULL & !used[0]) {
+        for b in triples(FULL & !used[1] & !a) {
+            let c = FULL & !a & !b;
+            if c & used[2] == 0 {
+                stacks.push([a, b, c]);
+            }
+        }
+    }
+    stacks
+}
+
+/// the rows of every order of the values down the columns of a stack,
+/// `row0 | row1 << 10 | row2 << 20`. with `fixed`, the first column is in one order only.
+fn rows(stack: Stack, fixed: bool) -> Vec<u32> {
+    let vals = stack.map(|mask| values(mask).map(|val| 1u32 << val));
+    let firsts = if fixed { &PERMS[..1] } else { &PERMS[..] };
+    let mut rows = Vec::with_capacity(216);
+    for a in firsts {
+        for b in PERMS.iter() {
+            for c in PERMS.iter() {
+                let mut key = 0;
+                for (x, shift) in [0, 10, 20].iter().enumerate() {
+                    key |= (vals[0][a[x]] | vals[1][b[x]] | vals[2][c[x]]) << shift;
+                }
+                rows.push(key);
+            }
+        }
+    }
+    rows
+}
+
+/// the ways to order a band for every choice of the values of its stacks,
+/// indexed by `(a * n1 + b) * n2 + c` for choice `a`, `b`, `c` of the stacks.
+///
+/// the rows of the last stack are what the first two leave. swapping rows keeps a band
+/// valid, so the first column is fixed and the count taken six times.
+fn orders(stacks: &[Vec<Stack>; 3]) -> Vec<u64> {
+    let firsts: Vec<Vec<u32>> = stacks[0].iter().map(|&s| rows(s, true)).collect();
+    let seconds: Vec<Vec<u32>> = stacks[1].iter().map(|&s| rows(s, false)).collect();
+    // the rows of every order of every choice of the last stack
+    let mut thirds: Vec<(u32, usize)> = Vec::with_capacity(stacks[2].len() * 216);
+    for (i, &stack) in stacks[2].iter().enumerate() {
+        thirds.extend(rows(stack, false).into_iter().map(|key| (key, i)));
+    }
+    thirds.sort_unstable();
+    let full = u32::from(FULL) * (1 | 1 << 10 | 1 << 20);
+    let n = stacks[2].len();
+    let mut counts = vec![0; firsts.len() * seconds.len() * n];
+    for (counts, (first, second)) in counts.chunks_mut(n).zip(
+        firsts
+            .iter()
+            .flat_map(|first| seconds.iter().map(move |second| (first, second))),
+    ) {
+        for &a in first.iter() {
+            for &b in second.iter() {
+                if a & b != 0 {
+                    continue;
+                }
+                let left = full & !a & !b;
+                let start = thirds.partition_point(|&(key, _)| key < left);
+                for &(_, i) in thirds[start..].iter().take_while(|&&(key, _)| key == left) {
+                    counts[i] += 6;
+                }
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SplitMix64;
+
+    /// a band from three rows of text.
+    fn band(text: &str) -> Band {
+        let mut cells = [0; 27];
+        for (cell, c) in cells.iter_mut().zip(text.bytes()) {
+            *cell = c - b'0';
+        }
+        Band::new(cells).unwrap()
+    }
+
+    #[test]
+    fn new() {
+        let mut cells = [0; 27];
+        cells[0] = 10;
+        assert!(matches!(Band::new(cells), Err(SuDoKuError::OutOfBound)));
+        cells[0] = 1;
+        cells[20] = 1;
+        assert!(matches!(Band::new(cells), Err(SuDoKuError::DuplicateValue)));
+        cells[20] = 0;
+        let band = Band::new(cells).unwrap();
+        assert_eq!(band.get(0, 0), Some(1));
+        assert_eq!(band.get(0, 1), None);
+        assert!(!band.is_full());
+        assert!(matches!(
+            band.completions(),
+            Err(SuDoKuError::InvalidFormat)
+        ));
+        assert_eq!(Band::from_board(&band.to_board()).unwrap(), band);
+        assert!(Band::from_board(&Board::new_diagonal()).is_err());
+    }
+
+    #[test]
+    fn walk() {
+        let mut last = None;
+        for band in bands().take(5000) {
+            assert!(band.is_full());
+            assert_eq!(band.cells()[..3], [1, 2, 3]);
+            assert_eq!(band.cells()[9..12], [4, 5, 6]);
+            assert!(Band::new(band.cells()).is_ok());
+            assert!(last < Some(band));
+            last = Some(band);
+        }
+    }
+
+    #[test]
+    fn class() {
+        let a = band("123456789456789123789123456");
+        // swap the last two rows, the first two columns and the last two boxes
+        let b = band("213789456879456123546123789");
+        assert_eq!(a.class().unwrap(), b.class().unwrap());
+        // the same columns in other rows
+        let c = band("123456789789123456456789123");
+        assert_eq!(a.class().unwrap(), c.class().unwrap());
+        let d = band("123456789456789123798123465");
+        assert_ne!(a.class().unwrap(), d.class().unwrap());
+        assert!(matches!(
+            band("123").class(),
+            Err(SuDoKuError::InvalidFormat)
+        ));
+    }
+
+    #[test]
+    fn orders_match_the_solver() {
+        // the third band of a grid counted both ways
+        let mut grid = Board::new();
+        DfsSolver::new().solve(&mut grid).unwrap();
+        let mut board = grid.clone();
+        let mut stacks: [Vec<Stack>; 3] = Default::default();
+        for (stack, choices) in stacks.iter_mut().enumerate() {
+            let mut cols = [0; 3];
+            for (i, mask) in cols.iter_mut().enumerate() {
+                for x in 6..9 {
+                    *mask |= 1 << grid.unchecked_get(x, stack * 3 + i).unwrap();
+                    board.unset(x, stack * 3 + i);
+                }
+            }
+            *choices = vec![cols];
+        }
+        let counted = orders(&stacks)[0];
+        let walked = DfsSolver::new().solutions(&board).count();
+        assert!(counted > 1);
+        assert_eq!(counted, walked as u64);
+    }
+
+    #[test]
+    fn random_bands() {
+        let mut rng = SplitMix64::new(11);
+        let mut seen = BTreeMap::new();
+        for _ in 0..2000 {
+            let band = random_band(&mut rng);
+            assert!(band.is_full());
+            assert_eq!(Band::new(band.cells()).unwrap(), band);
+            assert_eq!(band.cells()[18..21], [7, 8, 9]);
+            *seen.entry(band).or_insert(0) += 1;
+        }
+        // 2000 draws of 2,612,736 bands hardly ever repeat
+        assert!(seen.len() > 1990);
+    }
+
+    #[test]
+    fn random_grids() {
+        let mut rng = SplitMix64::new(3);
+        let a = random_grid(&mut rng);
+        let b = random_grid(&mut rng);
+        assert!(a.is_win() && b.is_win());
+        assert_ne!(a.to_string(), b.to_string());
+        assert_eq!(
+            random_grid(&mut SplitMix64::new(3)).to_string(),
+            a.to_string()
+        );
+    }
+
+    #[test]
+    fn stacks() {
+        let a = band("123456789456789123789123456");
+        let used = [
+            1 << 1 | 1 << 4 | 1 << 7,
+            1 << 2 | 1 << 5 | 1 << 8,
+            1 << 3 | 1 << 6 | 1 << 9,
+        ];
+        let stacks = next_stacks(used);
+        assert_eq!(stacks.len(), 56);
+        for cols in stacks {
+            assert_eq!(cols[0] | cols[1] | cols[2], FULL);
+            assert_eq!(cols[0] & used[0], 0);
+        }
+        assert!(a.completions().unwrap() > 0);
+    }
+
+    #[test]
+    #[ignore]
+    fn all_grids() {
+        // cargo test --release -- --ignored
+        let classes = band_classes();
+        assert_eq!(classes.len(), 44);
+        let most = classes
+            .iter()
+            .map(|(band, _)| band.completions().unwrap())
+            .max();
+        assert_eq!(most, Some(u128::from(MAX_COMPLETIONS)));
+        assert_eq!(classes.iter().map(|(_, size)| size).sum::<u64>(), 2_612_736);
+        assert_eq!(count_grids(), 6_670_903_752_021_072_936_960);
+    }
+}
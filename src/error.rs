@@ -1,4 +1,4 @@
-use std::fmt;
+use core::fmt;
 
 #[derive(Debug)]
 pub enum SuDoKuError {
@@ -6,6 +6,11 @@ pub enum SuDoKuError {
     OutOfBound,
     NotSolveable,
     DuplicateValue,
+    BudgetExhausted,
+    InvalidFormat,
+    InvalidRegion,
+    InvalidCage,
+    GivensNotReached,
 }
 
 impl fmt::Display for SuDoKuError {
@@ -14,4 +19,5 @@ impl fmt::Display for SuDoKuError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for SuDoKuError {}
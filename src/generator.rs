@@ -0,0 +1,703 @@
+//! generate sudoku with a unique solve.
+//! ```
+//! use sudoku_rs::{board::Board, generator::Generator, solver::DfsSolver};
+//!
+//! let mut g = Generator::new(42);
+//! let mut b = g.generate(&Board::new()).unwrap();
+//! assert!(DfsSolver::new().unique(&mut b).unwrap());
+//! ```
+//! the template decide the variant, a sudoku-x template gives sudoku-x puzzle.
+//! ```
+//! use sudoku_rs::{board::Board, generator::Generator};
+//!
+//! let mut g = Generator::new(7);
+//! let b = g.generate(&Board::new_diagonal()).unwrap();
+//! assert!(b.is_diagonal());
+//! ```
+//! killer and jigsaw puzzles grow a random layout from a solved board.
+//! ```
+//! use sudoku_rs::generator::{Generator, KillerOptions};
+//!
+//! let mut g = Generator::new(3);
+//! let options = KillerOptions::new().cage_sizes(&[0, 1, 1]).unwrap().givens(10);
+//! let b = g.killer(&options).unwrap();
+//! assert!(b.is_killer());
+//! assert!(b.cages().iter().all(|cage| cage.slots().len() <= 3));
+//! ```
+use crate::{
+    board::Board,
+    budget::Budget,
+    cage::Cage,
+    constraint::ORTHOGONAL,
+    enumerate,
+    error::SuDoKuError,
+    prelude::*,
+    rng::{Rng, SplitMix64},
+    solver::DfsSolver,
+};
+
+/// how many random values are put before solving.
+const SEED_SLOTS: usize = 11;
+/// give up a random start after this many nodes and try another one.
+const SEED_BUDGET: usize = 100_000;
+/// a clear is kept only if uniqueness is proven within this many nodes.
+const DIG_BUDGET: usize = 20_000;
+/// how many random starts before giving up.
+const MAX_ATTEMPTS: usize = 100;
+
+/// how many random layouts before giving up on the number of givens.
+const LAYOUT_ATTEMPTS: usize = 10;
+/// how many region swaps a jigsaw layout tries by default.
+const JIGSAW_SWAPS: usize = 3000;
+/// how many layout changes a puzzle with too many givens tries.
+const LAYOUT_CHANGES: usize = 30;
+/// how many region swaps one jigsaw layout change tries.
+const CHANGE_SWAPS: usize = 20;
+
+/// how to generate a killer puzzle.
+#[derive(Clone, Debug)]
+pub struct KillerOptions {
+    weights: Vec<u32>,
+    givens: Option<usize>,
+}
+
+impl Default for KillerOptions {
+    fn default() -> Self {
+        KillerOptions {
+            weights: vec![0, 4, 4, 2, 1],
+            givens: None,
+        }
+    }
+}
+
+impl KillerOptions {
+    /// return the default options, cages of 2 to 5 slots and as few givens as possible.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the weight of each cage size, `weights[0]` for one slot cages and so on.
+    /// a cage can end smaller when there is no room to grow.
+    /// fail with `SuDoKuError::InvalidFormat` if no size of `1..=9` has a weight.
+    pub fn cage_sizes(mut self, weights: &[u32]) -> Result<Self, SuDoKuError> {
+        let weights = &weights[..weights.len().min(9)];
+        let total = weights
+            .iter()
+            .try_fold(0u64, |total, &weight| total.checked_add(u64::from(weight)));
+        if matches!(total, None | Some(0)) {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        self.weights = weights.to_vec();
+        Ok(self)
+    }
+
+    /// stop digging at `givens` values, fail if it can not be reached.
+    pub fn givens(mut self, givens: usize) -> Self {
+        self.givens = Some(givens);
+        self
+    }
+}
+
+/// how to generate a jigsaw puzzle.
+#[derive(Clone, Debug)]
+pub struct JigsawOptions {
+    swaps: usize,
+    givens: Option<usize>,
+}
+
+impl Default for JigsawOptions {
+    fn default() -> Self {
+        JigsawOptions {
+            swaps: JIGSAW_SWAPS,
+            givens: None,
+        }
+    }
+}
+
+impl JigsawOptions {
+    /// return the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// try to move slots between regions this many times, more swaps give wilder regions.
+    pub fn swaps(mut self, swaps: usize) -> Self {
+        self.swaps = swaps;
+        self
+    }
+
+    /// stop digging at `givens` values, fail if it can not be reached.
+    pub fn givens(mut self, givens: usize) -> Self {
+        self.givens = Some(givens);
+        self
+    }
+}
+
+/// generate sudoku, reproducible with the same seed.
+#[derive(Clone, Debug)]
+pub struct Generator<R = SplitMix64> {
+    rng: R,
+    budget: Budget,
+}
+
+impl Generator {
+    /// return a new generator.
+    pub fn new(seed: u64) -> Self {
+        Generator::with_rng(SplitMix64::new(seed))
+    }
+}
+
+impl<R: Rng> Generator<R> {
+    /// return a new generator drawing from `rng`, reproducible with the same `rng` state.
+    /// ```
+    /// use sudoku_rs::{board::Board, generator::Generator, rng::SplitMix64};
+    ///
+    /// let a = Generator::with_rng(SplitMix64::new(6)).generate(&Board::new()).unwrap();
+    /// let b = Generator::new(6).generate(&Board::new()).unwrap();
+    /// assert_eq!(a.to_string(), b.to_string());
+    /// ```
+    pub fn with_rng(rng: R) -> Self {
+        Generator {
+            rng,
+            budget: Budget::new(),
+        }
+    }
+
+    /// give up with `SuDoKuError::BudgetExhausted` once a call runs out of `budget`.
+    /// a timeout counts from the start of each call, `max_nodes` caps each search of it.
+    /// ```
+    /// use sudoku_rs::{
+    ///     budget::{Budget, CancelToken},
+    ///     error::SuDoKuError,
+    ///     generator::{Generator, KillerOptions},
+    /// };
+    ///
+    /// let token = CancelToken::new();
+    /// token.cancel();
+    /// let mut g = Generator::new(2).with_budget(Budget::new().cancel_token(token));
+    /// let res = g.killer(&KillerOptions::new());
+    /// assert!(matches!(res, Err(SuDoKuError::BudgetExhausted)));
+    /// ```
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// fill a template into a random solved board.
+    /// values already in template are kept.
+    pub fn solution(&mut self, template: &Board) -> Result<Board, SuDoKuError> {
+        let budget = self.budget.started();
+        self.solution_in(template, &budget)
+    }
+
+    fn solution_in(&mut self, template: &Board, budget: &Budget) -> Result<Board, SuDoKuError> {
+        let solver = DfsSolver::with_budget(budget.capped(SEED_BUDGET));
+        let mut exhausted = false;
+        let mut empty: Vec<(usize, usize)> = (0..81)
+            .map(|pos| (pos / 9, pos % 9))
+            .filter(|&(x, y)| template.is_empty(x, y))
+            .collect();
+        for _ in 0..MAX_ATTEMPTS {
+            let mut board = template.clone();
+            self.rng.shuffle(&mut empty);
+            for &(x, y) in empty.iter().take(SEED_SLOTS) {
+                let avaliable = board.avaliable_val(x, y);
+                if !avaliable.is_empty() {
+                    let val = avaliable[self.rng.below(avaliable.len())];
+                    board.unchecked_set(x, y, val);
+                }
+            }
+            match solver.solve(&mut board) {
+                Ok(_) => return Ok(board),
+                Err(SuDoKuError::BudgetExhausted) if !budget.is_exhausted() => exhausted = true,
+                Err(SuDoKuError::NotSolveable) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        if exhausted {
+            return Err(SuDoKuError::BudgetExhausted);
+        }
+        Err(SuDoKuError::NotSolveable)
+    }
+
+    /// a classic grid drawn uniformly from all of them.
+    /// `solution` favours some grids over others, this does not, but takes longer.
+    /// ```
+    /// use sudoku_rs::generator::Generator;
+    ///
+    /// let grid = Generator::new(5).random_solution();
+    /// assert!(grid.is_win());
+    /// assert_eq!(grid.to_string(), Generator::new(5).random_solution().to_string());
+    /// ```
+    pub fn random_solution(&mut self) -> Board {
+        enumerate::random_grid(&mut self.rng)
+    }
+
+    /// generate a sudoku with a unique solve.
+    /// values already in template are kept as givens.
+    pub fn generate(&mut self, template: &Board) -> Result<Board, SuDoKuError> {
+        let budget = self.budget.started();
+        let mut board = self.solution_in(template, &budget)?;
+        self.dig(template, &mut board, 0, &budget)?;
+        Ok(board)
+    }
+
+    /// generate a killer sudoku.
+    /// cages are grown from a solved board, then values are cleared while the solve stay unique.
+    /// while too many values are left, cages are merged or split, a change is kept only if
+    /// the solve stay unique. fail with `SuDoKuError::GivensNotReached` if `givens` is not reached.
+    pub fn killer(&mut self, options: &KillerOptions) -> Result<Board, SuDoKuError> {
+        let max_size = options
+            .weights
+            .iter()
+            .rposition(|&weight| weight != 0)
+            .map_or(1, |i| i + 1);
+        let budget = self.budget.started();
+        let keep = options.givens.unwrap_or(0);
+        let reached = |givens: usize| options.givens.map_or(true, |target| givens <= target);
+        for _ in 0..LAYOUT_ATTEMPTS {
+            let solution = self.solution_in(&Board::new(), &budget)?;
+            let mut groups = self.groups(&solution, &options.weights);
+            let mut board = caged(&solution, &groups, &solution)?;
+            let mut givens = self.dig(
+                &caged(&solution, &groups, &Board::new())?,
+                &mut board,
+                keep,
+                &budget,
+            )?;
+            for _ in 0..LAYOUT_CHANGES {
+                if reached(givens) {
+                    break;
+                }
+                let mut next = groups.clone();
+                if !self.recage(&solution, &mut next, max_size) {
+                    continue;
+                }
+                let changed = caged(&solution, &next, &board)?;
+                if !proven_unique(&changed, &budget) {
+                    continue;
+                }
+                groups = next;
+                board = changed;
+                givens = self.dig(
+                    &caged(&solution, &groups, &Board::new())?,
+                    &mut board,
+                    keep,
+                    &budget,
+                )?;
+            }
+            if reached(givens) {
+                return Ok(board);
+            }
+        }
+        Err(SuDoKuError::GivensNotReached)
+    }
+
+    /// generate a jigsaw sudoku.
+    /// regions are reshaped from a solved board, then values are cleared while the solve stay unique.
+    /// while too many values are left, regions are reshaped a bit more, a change is kept only if
+    /// the solve stay unique. fail with `SuDoKuError::GivensNotReached` if `givens` is not reached.
+    /// ```
+    /// use sudoku_rs::{generator::{Generator, JigsawOptions}, solver::DfsSolver};
+    ///
+    /// let mut b = Generator::new(1).jigsaw(&JigsawOptions::new().givens(40)).unwrap();
+    /// assert!(b.is_jigsaw());
+    /// assert!(DfsSolver::new().unique(&mut b).unwrap());
+    /// ```
+    pub fn jigsaw(&mut self, options: &JigsawOptions) -> Result<Board, SuDoKuError> {
+        let budget = self.budget.started();
+        let keep = options.givens.unwrap_or(0);
+        let reached = |givens: usize| options.givens.map_or(true, |target| givens <= target);
+        for _ in 0..LAYOUT_ATTEMPTS {
+            let mut solution = self.solution_in(&Board::new(), &budget)?;
+            self.reshape(&mut solution, options.swaps)?;
+            let mut board = solution.clone();
+            let mut givens = self.dig(
+                &Board::with_regions(*board.regions())?,
+                &mut board,
+                keep,
+                &budget,
+            )?;
+            for _ in 0..LAYOUT_CHANGES {
+                if reached(givens) {
+                    break;
+                }
+                let mut next = solution.clone();
+                self.reshape(&mut next, CHANGE_SWAPS.min(options.swaps))?;
+                let mut changed = board.clone();
+                changed.set_regions(*next.regions())?;
+                if !proven_unique(&changed, &budget) {
+                    continue;
+                }
+                solution = next;
+                board = changed;
+                givens = self.dig(
+                    &Board::with_regions(*board.regions())?,
+                    &mut board,
+                    keep,
+                    &budget,
+                )?;
+            }
+            if reached(givens) {
+                return Ok(board);
+            }
+        }
+        Err(SuDoKuError::GivensNotReached)
+    }
+
+    /// split a solved board into random groups of slots, no value repeats in a group.
+    fn groups(&mut self, solution: &Board, weights: &[u32]) -> Vec<Vec<usize>> {
+        let total: u64 = weights.iter().map(|&weight| u64::from(weight)).sum();
+        let mut owner = [usize::MAX; 81];
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut order: Vec<usize> = (0..81).collect();
+        self.rng.shuffle(&mut order);
+        let value = |pos: usize| solution.unchecked_get(pos / 9, pos % 9).unwrap_or_default();
+        for start in order {
+            if owner[start] != usize::MAX {
+                continue;
+            }
+            // pick a size by weight
+            let mut pick = self.rng.below_u64(total);
+            let size = weights
+                .iter()
+                .position(|&weight| {
+                    if pick < u64::from(weight) {
+                        true
+                    } else {
+                        pick -= u64::from(weight);
+                        false
+                    }
+                })
+                .unwrap_or(0)
+                + 1;
+            let id = groups.len();
+            owner[start] = id;
+            let mut group = vec![start];
+            while group.len() < size {
+                let frontier: Vec<usize> = group
+                    .iter()
+                    .flat_map(|&pos| neighbours(pos))
+                    .filter(|&pos| {
+                        owner[pos] == usize::MAX && group.iter().all(|&g| value(g) != value(pos))
+                    })
+                    .collect();
+                if frontier.is_empty() {
+                    break;
+                }
+                let next = frontier[self.rng.below(frontier.len())];
+                owner[next] = id;
+                group.push(next);
+            }
+            groups.push(group);
+        }
+        groups
+    }
+
+    /// merge a group with a neighbouring one or split a slot off a group.
+    /// return false if nothing changed.
+    fn recage(&mut self, solution: &Board, groups: &mut Vec<Vec<usize>>, max_size: usize) -> bool {
+        let value = |pos: usize| solution.unchecked_get(pos / 9, pos % 9).unwrap_or_default();
+        let id = self.rng.below(groups.len());
+        if self.rng.below(2) == 0 {
+            let group = &groups[id];
+            let near: Vec<usize> = (0..groups.len())
+                .filter(|&other| {
+                    other != id
+                        && group.len() + groups[other].len() <= max_size
+                        && groups[other]
+                            .iter()
+                            .any(|&pos| neighbours(pos).any(|n| group.contains(&n)))
+                        && groups[other]
+                            .iter()
+                            .all(|&pos| group.iter().all(|&g| value(g) != value(pos)))
+                })
+                .collect();
+            if near.is_empty() {
+                return false;
+            }
+            let other = near[self.rng.below(near.len())];
+            let merged = groups.swap_remove(other);
+            // the last group took the place of `other`
+            let id = if id == groups.len() { other } else { id };
+            groups[id].extend(merged);
+        } else {
+            let mut rest = groups[id].clone();
+            if rest.len() < 2 {
+                return false;
+            }
+            let slot = rest.swap_remove(self.rng.below(rest.len()));
+            if !connected(&rest) {
+                return false;
+            }
+            groups[id] = rest;
+            groups.push(vec![slot]);
+        }
+        true
+    }
+
+    /// move slots between regions of a solved board, the board stays solved.
+    /// two slots with the same value in neighbouring regions swap their regions.
+    fn reshape(&mut self, board: &mut Board, swaps: usize) -> Result<(), SuDoKuError> {
+        let mut regions = *board.regions();
+        for _ in 0..swaps {
+            let a = self.rng.below(81);
+            let near: Vec<usize> = neighbours(a)
+                .filter(|&pos| regions[pos] != regions[a])
+                .collect();
+            if near.is_empty() {
+                continue;
+            }
+            let other = regions[near[self.rng.below(near.len())]];
+            let value = board.unchecked_get(a / 9, a % 9);
+            // the slot of the other region holding the same value
+            let b = match (0..81).find(|&pos| {
+                regions[pos] == other && board.unchecked_get(pos / 9, pos % 9) == value
+            }) {
+                Some(b) => b,
+                None => continue,
+            };
+            let mut next = regions;
+            next[b] = regions[a];
+            next[a] = other;
+            if Board::with_regions(next).is_ok() {
+                regions = next;
+            }
+        }
+        board.set_regions(regions)
+    }
+
+    /// clear slots of a solved board while the solve stay unique.
+    /// stop when `keep` values are left, return how many values are left.
+    fn dig(
+        &mut self,
+        template: &Board,
+        board: &mut Board,
+        keep: usize,
+        budget: &Budget,
+    ) -> Result<usize, SuDoKuError> {
+        let solver = DfsSolver::with_budget(budget.capped(DIG_BUDGET));
+        let mut filled: Vec<(usize, usize)> = (0..81)
+            .map(|pos| (pos / 9, pos % 9))
+            .filter(|&(x, y)| template.is_empty(x, y) && !board.is_empty(x, y))
+            .collect();
+        self.rng.shuffle(&mut filled);
+        let mut left = filled.len();
+        for (x, y) in filled {
+            if left <= keep {
+                break;
+            }
+            let val = board.unchecked_get(x, y).ok_or(SuDoKuError::NotSolveable)?;
+            board.unset(x, y);
+            match solver.unique(&mut board.clone()) {
+                Ok(true) => left -= 1,
+                Ok(false) => board.unchecked_set(x, y, val),
+                Err(SuDoKuError::BudgetExhausted) if !budget.is_exhausted() => {
+                    board.unchecked_set(x, y, val)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(left)
+    }
+}
+
+/// the orthogonal neighbours of a slot.
+fn neighbours(pos: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = ((pos / 9) as isize, (pos % 9) as isize);
+    ORTHOGONAL
+        .iter()
+        .map(move |&(dx, dy)| (x + dx, y + dy))
+        .filter(|(nx, ny)| (0..9).contains(nx) && (0..9).contains(ny))
+        .map(|(nx, ny)| (nx * 9 + ny) as usize)
+}
+
+/// check the slots are one orthogonally connected group.
+fn connected(slots: &[usize]) -> bool {
+    let mut seen = vec![slots[0]];
+    let mut i = 0;
+    while i < seen.len() {
+        for pos in neighbours(seen[i]) {
+            if slots.contains(&pos) && !seen.contains(&pos) {
+                seen.push(pos);
+            }
+        }
+        i += 1;
+    }
+    seen.len() == slots.len()
+}
+
+/// a board with a cage for each group summed from `solution`, and the values of `values`.
+fn caged(solution: &Board, groups: &[Vec<usize>], values: &Board) -> Result<Board, SuDoKuError> {
+    let mut board = Board::new();
+    for group in groups {
+        let sum = group
+            .iter()
+            .map(|&pos| solution.unchecked_get(pos / 9, pos % 9).unwrap_or_default())
+            .sum();
+        let slots = group.iter().map(|&pos| (pos / 9, pos % 9)).collect();
+        board.add_cage(Cage::new(sum, slots)?)?;
+    }
+    for pos in 0..81 {
+        if let Some(val) = values.unchecked_get(pos / 9, pos % 9) {
+            board.unchecked_set(pos / 9, pos % 9, val);
+        }
+    }
+    Ok(board)
+}
+
+/// check the solve is unique within the budget of a clear.
+fn proven_unique(board: &Board, budget: &Budget) -> bool {
+    let solver = DfsSolver::with_budget(budget.capped(DIG_BUDGET));
+    matches!(solver.unique(&mut board.clone()), Ok(true))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn solution() {
+        let mut g = Generator::new(1);
+        let b = g.solution(&Board::new()).unwrap();
+        assert!(b.is_win());
+    }
+
+    #[test]
+    fn with_rng() {
+        /// the same number over and over.
+        struct Stuck;
+
+        impl Rng for Stuck {
+            fn next_u64(&mut self) -> u64 {
+                u64::MAX / 3
+            }
+        }
+
+        let mut g = Generator::with_rng(Stuck);
+        let a = g.generate(&Board::new()).unwrap();
+        let b = g.generate(&Board::new()).unwrap();
+        assert!(DfsSolver::new().unique(&mut a.clone()).unwrap());
+        assert_eq!(a.to_string(), b.to_string());
+    }
+
+    #[test]
+    fn random_solution() {
+        let a = Generator::new(8).random_solution();
+        assert!(a.is_win());
+        assert_eq!(
+            Generator::new(8).random_solution().to_string(),
+            a.to_string()
+        );
+        assert_ne!(
+            Generator::new(9).random_solution().to_string(),
+            a.to_string()
+        );
+    }
+
+    #[test]
+    fn reproducible() {
+        let a = Generator::new(3).generate(&Board::new()).unwrap();
+        let b = Generator::new(3).generate(&Board::new()).unwrap();
+        assert_eq!(a.to_string(), b.to_string());
+    }
+
+    #[test]
+    fn diagonal() {
+        let mut g = Generator::new(5);
+        let solution = g.solution(&Board::new_diagonal()).unwrap();
+        assert!(solution.is_diagonal());
+        assert!(solution.is_win());
+        let mut puzzle = g.generate(&Board::new_diagonal()).unwrap();
+        assert!(DfsSolver::new().unique(&mut puzzle.clone()).unwrap());
+        DfsSolver::new().solve(&mut puzzle).unwrap();
+        assert!(puzzle.is_win());
+    }
+
+    #[test]
+    fn killer() {
+        let options = KillerOptions::new()
+            .cage_sizes(&[0, 2, 1])
+            .unwrap()
+            .givens(20);
+        let mut puzzle = Generator::new(11).killer(&options).unwrap();
+        assert!(puzzle.cages().iter().all(|cage| cage.slots().len() <= 3));
+        let givens = (0..81)
+            .filter(|&pos| !puzzle.is_empty(pos / 9, pos % 9))
+            .count();
+        assert!(givens <= 20);
+        assert!(DfsSolver::new().unique(&mut puzzle.clone()).unwrap());
+        DfsSolver::new().solve(&mut puzzle).unwrap();
+        assert!(puzzle.is_win());
+    }
+
+    #[test]
+    fn cage_sizes() {
+        for weights in [
+            &[][..],
+            &[0, 0, 0],
+            &[0; 9],
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        ]
+        .iter()
+        {
+            assert!(matches!(
+                KillerOptions::new().cage_sizes(weights),
+                Err(SuDoKuError::InvalidFormat)
+            ));
+        }
+        // the weights sum past u32
+        let options = KillerOptions::new()
+            .cage_sizes(&[0, u32::MAX, u32::MAX])
+            .unwrap();
+        let mut puzzle = Generator::new(5).killer(&options).unwrap();
+        assert!(puzzle.cages().iter().all(|cage| cage.slots().len() <= 3));
+        assert!(DfsSolver::new().unique(&mut puzzle).unwrap());
+    }
+
+    #[test]
+    fn jigsaw() {
+        let a = Generator::new(4).jigsaw(&JigsawOptions::new()).unwrap();
+        let b = Generator::new(4).jigsaw(&JigsawOptions::new()).unwrap();
+        assert_eq!(a.to_string(), b.to_string());
+        assert!(a.is_jigsaw());
+        assert!(DfsSolver::new().unique(&mut a.clone()).unwrap());
+        // no swap keeps the boxes
+        let c = Generator::new(4)
+            .jigsaw(&JigsawOptions::new().swaps(0))
+            .unwrap();
+        assert!(!c.is_jigsaw());
+    }
+
+    #[test]
+    fn layout_changes() {
+        // the first layouts stop short of 22 givens
+        let mut b = Generator::new(2)
+            .jigsaw(&JigsawOptions::new().givens(22))
+            .unwrap();
+        let givens = (0..81).filter(|&pos| !b.is_empty(pos / 9, pos % 9)).count();
+        assert!(givens <= 22);
+        assert!(DfsSolver::new().unique(&mut b).unwrap());
+        // no layout is unique without givens
+        assert!(matches!(
+            Generator::new(2).jigsaw(&JigsawOptions::new().givens(0)),
+            Err(SuDoKuError::GivensNotReached)
+        ));
+    }
+
+    #[test]
+    fn connected_groups() {
+        assert!(connected(&[0, 1, 10]));
+        assert!(!connected(&[0, 10]));
+        assert_eq!(neighbours(0).collect::<Vec<_>>(), vec![1, 9]);
+        assert_eq!(neighbours(40).count(), 4);
+    }
+
+    #[test]
+    fn keep_template() {
+        let mut template = Board::new();
+        template.set(4, 4, 5).unwrap();
+        let b = Generator::new(9).generate(&template).unwrap();
+        assert_eq!(b.get(4, 4).unwrap(), Some(5));
+    }
+}
@@ -9,7 +9,10 @@
 //! ```
 //! this mod doesn't care of unique.
 //! but inunique sudoku's complexity will be higher on average.
-use crate::board;
+use crate::{board, budget::Budget, error::SuDoKuError, prelude::*, trace::Observer};
+
+/// default limit of dfs complexity.
+const MAX_COMPLEXITY: usize = 100_000;
 
 /// instand of grand return.
 /// dfs complexity show us how hard to find a correct solution.
@@ -20,17 +23,88 @@ pub struct Score {
     pub empty_slot_count: usize,
 }
 
-#[derive(Default, Copy, Clone)]
-pub struct Grade;
+/// grade a sudoku by the complexity of its dfs solve.
+/// it holds a budget, so it is not `Copy` since 0.3, clone it instead.
+#[derive(Clone)]
+pub struct Grade {
+    max_complexity: usize,
+    budget: Budget,
+}
+
+impl Default for Grade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Grade {
     /// return a new Grade struct.
     pub fn new() -> Self {
-        Grade {}
+        Grade {
+            max_complexity: MAX_COMPLEXITY,
+            budget: Budget::new(),
+        }
+    }
+
+    /// return a new Grade struct which stops once a grade runs out of `budget`.
+    pub fn with_budget(budget: Budget) -> Self {
+        Grade {
+            max_complexity: MAX_COMPLEXITY,
+            budget,
+        }
+    }
+
+    /// stop counting once dfs complexity is over `max_complexity`.
+    /// default is 100_000.
+    pub fn max_complexity(mut self, max_complexity: usize) -> Self {
+        self.max_complexity = max_complexity;
+        self
     }
 
     /// grade a sudoku.
-    pub fn grade(self, target: &mut board::Board) -> Score {
+    /// if the budget is exhausted, silently return the partial score counted so far,
+    /// use `try_grade` to tell it apart from a full score.
+    pub fn grade(&self, target: &mut board::Board) -> Score {
+        self.grade_with(target, &mut ())
+    }
+
+    /// grade a sudoku, report the search to observer.
+    pub fn grade_with<O: Observer>(&self, target: &mut board::Board, observer: &mut O) -> Score {
+        self.grade_do(target, observer).0
+    }
+
+    /// grade a sudoku.
+    /// fail with `SuDoKuError::BudgetExhausted` if the budget is exhausted.
+    /// ```
+    /// use sudoku_rs::{board::Board, budget::Budget, grade::Grade};
+    ///
+    /// let mut b = Board::new();
+    /// let g = Grade::with_budget(Budget::new().max_nodes(100));
+    /// assert!(g.try_grade(&mut b).is_err());
+    /// ```
+    pub fn try_grade(&self, target: &mut board::Board) -> Result<Score, SuDoKuError> {
+        self.try_grade_with(target, &mut ())
+    }
+
+    /// grade a sudoku, report the search to observer.
+    /// fail with `SuDoKuError::BudgetExhausted` if the budget is exhausted.
+    pub fn try_grade_with<O: Observer>(
+        &self,
+        target: &mut board::Board,
+        observer: &mut O,
+    ) -> Result<Score, SuDoKuError> {
+        let (score, res) = self.grade_do(target, observer);
+        res.map(|_| score)
+    }
+
+    fn grade_do<O: Observer>(
+        &self,
+        target: &mut board::Board,
+        observer: &mut O,
+    ) -> (Score, Result<(), SuDoKuError>) {
+        observer.start();
+        let mut meter = self.budget.meter();
+        let mut res = Ok(());
         let mut queue = Vec::with_capacity(81);
         for x in 0..9 {
             for y in 0..9 {
@@ -41,6 +115,7 @@ impl Grade {
             }
         }
         // avaliable count no use now
+        #[allow(clippy::unnecessary_sort_by)]
         queue.sort_unstable_by(|a, b| a.2.cmp(&b.2));
         queue.iter_mut().for_each(|item| item.2 = 0);
         // do the dfs
@@ -50,13 +125,24 @@ impl Grade {
         while let Some((rx, ry, ind)) = queue.get_mut(cur) {
             let x = *rx;
             let y = *ry;
-            if let Some(upper_than_now) = target.avaliable_val(x, y).get(*ind) {
+            if let Err(e) = meter.tick() {
+                res = Err(e);
+                break;
+            }
+            let avaliable = target.avaliable_val(x, y);
+            if let Some(upper_than_now) = avaliable.get(*ind) {
                 cur += 1;
                 *ind += 1;
                 target.unchecked_set(x, y, *upper_than_now);
+                if avaliable.len() == 1 {
+                    observer.place(x, y, *upper_than_now, cur);
+                } else {
+                    observer.guess(x, y, *upper_than_now, cur);
+                }
             } else {
                 // no avaliable value
                 dfs_complexity += 1;
+                observer.backtrack(x, y, cur);
                 if cur != 0 {
                     cur -= 1;
                     target.unset(x, y);
@@ -68,24 +154,27 @@ impl Grade {
             }
             // no finsih even when sudoku complete
             if cur == queue.len() {
-                if dfs_complexity > 100_000 {
-                    // doesn't count more than 100_000
+                if dfs_complexity > self.max_complexity {
+                    // doesn't count more than max complexity
                     break;
                 }
                 cur = cur.saturating_sub(2);
                 target.unset(x, y);
             }
         }
-        Score {
+        observer.finish();
+        let score = Score {
             dfs_complexity,
             empty_slot_count: queue.len(),
-        }
+        };
+        (score, res)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::trace::SolveStats;
 
     #[test]
     fn grade_0() {
@@ -111,6 +200,43 @@ mod test {
         assert_eq!(score.dfs_complexity, 2);
     }
 
+    #[test]
+    fn grade_budget() {
+        let mut b = board::Board::from_vec(vec![
+            0, 0, 0, 2, 0, 8, 7, 0, 9, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 3, 0, 0, 0, 0, 8,
+            7, 0, 0, 4, 3, 0, 5, 6, 0, 0, 0, 5, 9, 0, 0, 1, 1, 9, 0, 3, 0, 2, 0, 0, 0, 9, 0, 8, 5,
+            2, 6, 1, 0, 3, 5, 1, 6, 4, 3, 7, 9, 2, 8, 4, 2, 0, 8, 0, 0, 6, 5, 7,
+        ]);
+        let g = Grade::with_budget(Budget::new().max_nodes(5));
+        assert!(matches!(
+            g.try_grade(&mut b.clone()),
+            Err(SuDoKuError::BudgetExhausted)
+        ));
+        let score = g.grade(&mut b);
+        assert_eq!(score.empty_slot_count, 38);
+    }
+
+    #[test]
+    fn grade_max_complexity() {
+        let mut b = board::Board::new();
+        let score = Grade::new().max_complexity(10).grade(&mut b);
+        assert!(score.dfs_complexity > 10);
+        assert!(score.dfs_complexity < 1000);
+    }
+
+    #[test]
+    fn grade_stats() {
+        let mut b = board::Board::from_vec(vec![
+            0, 0, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 0, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        let mut stats = SolveStats::new();
+        let score = Grade::new().grade_with(&mut b, &mut stats);
+        assert_eq!(stats.backtracks, score.dfs_complexity);
+        assert_eq!(stats.max_depth, 3);
+    }
+
     #[test]
     fn grade_n() {
         let mut b = board::Board::from_vec(vec![
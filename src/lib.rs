@@ -29,9 +29,37 @@
 //! let scores = g.grade(&mut b);
 //! println!("{:?}", scores);
 //! ```
+//!
+//! the `std` feature is on by default. without it the crate needs only `core` and `alloc`,
+//! and `solver::DfsSolver::solve_fixed` solves a classic board without allocation.
+//! on targets without pointer sized atomics there is no `budget::CancelToken`,
+//! and constraints are shared by `Rc` instead of `Arc`.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
-mod bitset;
+pub mod bitset;
 pub mod board;
+pub mod budget;
+pub mod cage;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+pub mod collection;
+pub mod constraint;
+pub mod enumerate;
 pub mod error;
+pub mod generator;
 pub mod grade;
+pub mod multi;
+pub mod packed;
+mod prelude;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rng;
+pub mod sat;
 pub mod solver;
+pub mod symmetry;
+pub mod trace;
+#[cfg(feature = "wasm")]
+pub mod wasm;
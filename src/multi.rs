@@ -0,0 +1,398 @@
+//! puzzles made of several overlapping grids, like samurai sudoku.
+//!
+//! ```
+//! use sudoku_rs::{multi::MultiBoard, solver::DfsSolver};
+//!
+//! let mut b = MultiBoard::samurai();
+//! b.set(0, 8, 8, 5).unwrap();
+//! // the bottom right box of the first grid is the top left box of the center
+//! assert_eq!(b.get(2, 2, 2).unwrap(), Some(5));
+//! DfsSolver::new().solve_multi(&mut b).unwrap();
+//! assert!(b.is_win());
+//! ```
+//! in the text format every grid is a board, separated by an empty line.
+//! shared boxes are `share: grid box grid box` lines, all counted from 1.
+//! ```
+//! use sudoku_rs::multi::MultiBoard;
+//!
+//! let text = "1\n\n.\nshare: 1 1 2 9";
+//! let b: MultiBoard = text.parse().unwrap();
+//! assert_eq!(b.grids().len(), 2);
+//! assert_eq!(b.get(1, 6, 6).unwrap(), Some(1));
+//! assert_eq!(b.to_string().parse::<MultiBoard>().unwrap().to_string(), b.to_string());
+//! ```
+use crate::{bitset::CandidateSet, board::Board, error::SuDoKuError, prelude::*, solver::Puzzle};
+use core::fmt;
+
+/// a 3x3 box shared by two grids.
+/// boxes are numbered 0..9 from top left, row by row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub grid: usize,
+    pub block: usize,
+    pub other_grid: usize,
+    pub other_block: usize,
+}
+
+/// the `i`th slot of a 3x3 box, as index of board.
+fn block_pos(block: usize, i: usize) -> usize {
+    (block / 3 * 3 + i / 3) * 9 + block % 3 * 3 + i % 3
+}
+
+/// several grids, a slot in a shared box belongs to every grid contain it.
+#[derive(Clone)]
+pub struct MultiBoard {
+    grids: Vec<Board>,
+    shares: Vec<Share>,
+    /// the cell id of every slot of every grid.
+    links: Vec<[usize; 81]>,
+    /// every (grid, pos) of a cell.
+    cells: Vec<Vec<(usize, usize)>>,
+}
+
+impl MultiBoard {
+    /// return a new puzzle.
+    /// values of a shared slot must be the same in every grid, or be empty.
+    pub fn new(grids: Vec<Board>, shares: Vec<Share>) -> Result<Self, SuDoKuError> {
+        let count = grids.len();
+        // union find over every slot of every grid
+        let mut parent: Vec<usize> = (0..count * 81).collect();
+        fn find(parent: &mut [usize], i: usize) -> usize {
+            let mut root = i;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[i] = root;
+            root
+        }
+        for share in shares.iter() {
+            if share.grid >= count
+                || share.other_grid >= count
+                || share.block >= 9
+                || share.other_block >= 9
+            {
+                return Err(SuDoKuError::OutOfBound);
+            }
+            for i in 0..9 {
+                let a = find(&mut parent, share.grid * 81 + block_pos(share.block, i));
+                let b = find(
+                    &mut parent,
+                    share.other_grid * 81 + block_pos(share.other_block, i),
+                );
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+        let mut links = vec![[0; 81]; count];
+        let mut cells: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut ids = vec![usize::MAX; count * 81];
+        for i in 0..count * 81 {
+            let root = find(&mut parent, i);
+            if ids[root] == usize::MAX {
+                ids[root] = cells.len();
+                cells.push(Vec::new());
+            }
+            links[i / 81][i % 81] = ids[root];
+            cells[ids[root]].push((i / 81, i % 81));
+        }
+        let mut board = MultiBoard {
+            grids,
+            shares,
+            links,
+            cells,
+        };
+        // spread the values of shared slots
+        for id in 0..board.cells.len() {
+            let mut value = None;
+            for &(grid, pos) in board.cells[id].iter() {
+                match (value, board.grids[grid].unchecked_get(pos / 9, pos % 9)) {
+                    (Some(a), Some(b)) if a != b => return Err(SuDoKuError::DuplicateValue),
+                    (None, Some(b)) => value = Some(b),
+                    _ => {}
+                }
+            }
+            if let Some(val) = value {
+                board.place_cell(id, val);
+            }
+        }
+        Ok(board)
+    }
+
+    /// return an empty samurai sudoku.
+    /// grids are top left, top right, center, bottom left and bottom right.
+    pub fn samurai() -> Self {
+        let share = |grid, block, other_block| Share {
+            grid,
+            block,
+            other_grid: 2,
+            other_block,
+        };
+        let shares = vec![
+            share(0, 8, 0),
+            share(1, 6, 2),
+            share(3, 2, 6),
+            share(4, 0, 8),
+        ];
+        MultiBoard::new(vec![Board::new(); 5], shares).expect("samurai layout")
+    }
+
+    /// the grids.
+    pub fn grids(&self) -> &[Board] {
+        &self.grids
+    }
+
+    /// the shared boxes.
+    pub fn shares(&self) -> &[Share] {
+        &self.shares
+    }
+
+    /// set value of a slot in every grid contain it.
+    pub fn set(&mut self, grid: usize, x: usize, y: usize, val: u8) -> Result<(), SuDoKuError> {
+        if grid >= self.grids.len() || x >= 9 || y >= 9 {
+            return Err(SuDoKuError::OutOfBound);
+        }
+        if !(1..=9).contains(&val) {
+            return Err(SuDoKuError::InvalidValue);
+        }
+        self.place_cell(self.links[grid][x * 9 + y], val);
+        Ok(())
+    }
+
+    /// get value of a slot.
+    pub fn get(&self, grid: usize, x: usize, y: usize) -> Result<Option<u8>, SuDoKuError> {
+        if grid >= self.grids.len() || x >= 9 || y >= 9 {
+            return Err(SuDoKuError::OutOfBound);
+        }
+        Ok(self.grids[grid].unchecked_get(x, y))
+    }
+
+    /// clear a slot in every grid contain it.
+    pub fn unset(&mut self, grid: usize, x: usize, y: usize) {
+        if grid < self.grids.len() && x < 9 && y < 9 {
+            self.clear_cell(self.links[grid][x * 9 + y]);
+        }
+    }
+
+    /// check if every grid is filled.
+    pub fn is_win(&self) -> bool {
+        self.grids.iter().all(|grid| grid.is_win())
+    }
+
+    fn place_cell(&mut self, id: usize, val: u8) {
+        for &(grid, pos) in self.cells[id].iter() {
+            self.grids[grid].unchecked_set(pos / 9, pos % 9, val);
+        }
+    }
+
+    fn clear_cell(&mut self, id: usize) {
+        for &(grid, pos) in self.cells[id].iter() {
+            self.grids[grid].unset(pos / 9, pos % 9);
+        }
+    }
+}
+
+/// slots are addressed as `(grid, x * 9 + y)`.
+impl Puzzle for MultiBoard {
+    fn empty_slots(&self) -> Vec<(usize, usize)> {
+        // fill the most shared grids first, the others are then a grid with fixed boxes
+        let mut order: Vec<usize> = (0..self.grids.len()).collect();
+        order.sort_by_key(|&grid| {
+            let shared = self
+                .shares
+                .iter()
+                .filter(|share| share.grid == grid || share.other_grid == grid)
+                .count();
+            core::cmp::Reverse(shared)
+        });
+        let mut seen = vec![false; self.cells.len()];
+        let mut slots = Vec::new();
+        for grid in order {
+            for pos in 0..81 {
+                let id = self.links[grid][pos];
+                if !seen[id] && self.grids[grid].is_empty(pos / 9, pos % 9) {
+                    seen[id] = true;
+                    slots.push((grid, pos));
+                }
+            }
+        }
+        slots
+    }
+
+    fn candidates(&self, grid: usize, pos: usize) -> CandidateSet {
+        self.cells[self.links[grid][pos]]
+            .iter()
+            .map(|&(grid, pos)| self.grids[grid].blocked(pos / 9, pos % 9))
+            .fold(CandidateSet::new(), |a, b| a | b)
+            .complement()
+    }
+
+    fn avaliable_count(&self, grid: usize, pos: usize) -> usize {
+        self.candidates(grid, pos).count()
+    }
+
+    fn place(&mut self, grid: usize, pos: usize, val: u8) {
+        self.place_cell(self.links[grid][pos], val);
+    }
+
+    fn clear(&mut self, grid: usize, pos: usize) {
+        self.clear_cell(self.links[grid][pos]);
+    }
+
+    fn feasible(&self, grid: usize, pos: usize) -> bool {
+        self.cells[self.links[grid][pos]]
+            .iter()
+            .all(|&(grid, pos)| self.grids[grid].cage_feasible(pos / 9, pos % 9))
+    }
+}
+
+impl fmt::Display for MultiBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, grid) in self.grids.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", grid)?;
+        }
+        for share in self.shares.iter() {
+            writeln!(
+                f,
+                "share: {} {} {} {}",
+                share.grid + 1,
+                share.block + 1,
+                share.other_grid + 1,
+                share.other_block + 1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for MultiBoard {
+    type Err = SuDoKuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut shares = Vec::new();
+        let mut blocks = vec![String::new()];
+        for line in s.lines() {
+            if let Some(value) = line.trim().strip_prefix("share:") {
+                let nums = value
+                    .split_whitespace()
+                    .map(|word| match word.parse::<usize>() {
+                        Ok(n) if n >= 1 => Ok(n - 1),
+                        _ => Err(SuDoKuError::InvalidFormat),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if nums.len() != 4 {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+                shares.push(Share {
+                    grid: nums[0],
+                    block: nums[1],
+                    other_grid: nums[2],
+                    other_block: nums[3],
+                });
+            } else if line.trim().is_empty() {
+                blocks.push(String::new());
+            } else {
+                let block = blocks.last_mut().expect("at least one block");
+                block.push_str(line);
+                block.push('\n');
+            }
+        }
+        let grids = blocks
+            .iter()
+            .filter(|block| !block.is_empty())
+            .map(|block| block.parse())
+            .collect::<Result<Vec<Board>, _>>()?;
+        MultiBoard::new(grids, shares)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::DfsSolver;
+
+    #[test]
+    fn shared_slot() {
+        let mut b = MultiBoard::samurai();
+        b.set(2, 8, 8, 4).unwrap();
+        assert_eq!(b.get(4, 2, 2).unwrap(), Some(4));
+        assert!(!b.grids()[4].avaliable_val(2, 0).contains(&4));
+        b.unset(4, 2, 2);
+        assert_eq!(b.get(2, 8, 8).unwrap(), None);
+    }
+
+    #[test]
+    fn conflict() {
+        let mut a = Board::new();
+        a.set(0, 0, 1).unwrap();
+        let mut b = Board::new();
+        b.set(0, 0, 2).unwrap();
+        let share = Share {
+            grid: 0,
+            block: 0,
+            other_grid: 1,
+            other_block: 0,
+        };
+        assert!(MultiBoard::new(vec![a, b], vec![share]).is_err());
+    }
+
+    #[test]
+    fn avaliable() {
+        let mut b = MultiBoard::samurai();
+        // the first row of the center is partly the seventh row of the top right grid
+        b.set(1, 6, 5, 3).unwrap();
+        assert!(!b.candidates(2, 6).get(3).unwrap());
+        assert!(b.candidates(2, 5).get(3).unwrap());
+    }
+
+    #[test]
+    fn solve() {
+        let mut b = MultiBoard::samurai();
+        let solver = DfsSolver::new();
+        solver.solve_multi(&mut b).unwrap();
+        assert!(b.is_win());
+        for share in b.shares() {
+            for i in 0..9 {
+                let pos = block_pos(share.block, i);
+                let other = block_pos(share.other_block, i);
+                assert_eq!(
+                    b.get(share.grid, pos / 9, pos % 9).unwrap(),
+                    b.get(share.other_grid, other / 9, other % 9).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unique() {
+        let mut solved = MultiBoard::samurai();
+        let solver = DfsSolver::new();
+        solver.solve_multi(&mut solved).unwrap();
+        let mut puzzle = solved.clone();
+        assert!(solver.unique_multi(&mut puzzle.clone()).unwrap());
+        // a few holes in every grid can only be filled back one way
+        for grid in 0..5 {
+            for &(x, y) in [(0, 0), (4, 4), (8, 8), (1, 7)].iter() {
+                puzzle.unset(grid, x, y);
+            }
+        }
+        assert!(solver.unique_multi(&mut puzzle.clone()).unwrap());
+        assert!(!solver.unique_multi(&mut MultiBoard::samurai()).unwrap());
+        solver.solve_multi(&mut puzzle).unwrap();
+        assert_eq!(puzzle.to_string(), solved.to_string());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut b = MultiBoard::samurai();
+        b.set(0, 0, 0, 1).unwrap();
+        b.set(2, 0, 0, 9).unwrap();
+        let text = b.to_string();
+        assert!(text.ends_with("share: 1 9 3 1\nshare: 2 7 3 3\nshare: 4 3 3 7\nshare: 5 1 3 9\n"));
+        let parsed: MultiBoard = text.parse().unwrap();
+        assert_eq!(parsed.shares(), b.shares());
+        assert_eq!(parsed.get(0, 6, 6).unwrap(), Some(9));
+        assert_eq!(parsed.to_string(), text);
+    }
+}
@@ -0,0 +1,495 @@
+//! a packed board for bulk solving.
+//!
+//! a digit keeps the 81 slots it can still go as bits of a `u128`,
+//! so a placement or a singles search touches every slot at once.
+//! only the classic rules are known, variants stay with `Board`.
+//! ```
+//! use sudoku_rs::packed::{PackedBoard, PackedSolver};
+//!
+//! let mut b: PackedBoard = "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......"
+//!     .parse()
+//!     .unwrap();
+//! let solver = PackedSolver::new();
+//! assert!(solver.unique(&b).unwrap());
+//! solver.solve(&mut b).unwrap();
+//! assert!(b.is_solved());
+//! assert!(b.to_board().is_win());
+//! ```
+use crate::{
+    bitset::CandidateSet,
+    board::Board,
+    budget::{Budget, Meter},
+    error::SuDoKuError,
+    prelude::*,
+};
+use core::{convert::TryFrom, fmt};
+
+/// the bits of all 81 slots.
+const ALL: u128 = (1 << 81) - 1;
+
+/// the slots of every row, column and box.
+const UNITS: [u128; 27] = units();
+
+/// the slots sharing a unit with a slot, the slot itself not included.
+const PEERS: [u128; 81] = peers();
+
+const fn units() -> [u128; 27] {
+    let mut units = [0; 27];
+    let mut pos = 0;
+    while pos < 81 {
+        let (x, y) = (pos / 9, pos % 9);
+        units[x] |= 1 << pos;
+        units[9 + y] |= 1 << pos;
+        units[18 + x / 3 * 3 + y / 3] |= 1 << pos;
+        pos += 1;
+    }
+    units
+}
+
+const fn peers() -> [u128; 81] {
+    let units = units();
+    let mut peers = [0; 81];
+    let mut pos = 0;
+    while pos < 81 {
+        let (x, y) = (pos / 9, pos % 9);
+        peers[pos] = (units[x] | units[9 + y] | units[18 + x / 3 * 3 + y / 3]) & !(1 << pos);
+        pos += 1;
+    }
+    peers
+}
+
+/// the slot of the lowest bit.
+fn lowest(bits: u128) -> usize {
+    bits.trailing_zeros() as usize
+}
+
+/// a classic board as one 81 bit mask per digit.
+/// boards with the same givens are equal and hash the same, handy for deduplication.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedBoard {
+    /// the slots where a digit can go, or is.
+    cand: [u128; 9],
+    /// the slots with a value.
+    solved: u128,
+}
+
+impl Default for PackedBoard {
+    fn default() -> Self {
+        PackedBoard::new()
+    }
+}
+
+impl PackedBoard {
+    /// return an empty board.
+    pub fn new() -> Self {
+        PackedBoard {
+            cand: [ALL; 9],
+            solved: 0,
+        }
+    }
+
+    /// set a value, `pos` is `x * 9 + y`.
+    /// fail if the value can not be put there.
+    pub fn set(&mut self, pos: usize, val: u8) -> Result<(), SuDoKuError> {
+        if pos >= 81 {
+            return Err(SuDoKuError::OutOfBound);
+        }
+        if !(1..=9).contains(&val) {
+            return Err(SuDoKuError::InvalidValue);
+        }
+        if self.get(pos).is_some() || self.cand[val as usize - 1] & 1 << pos == 0 {
+            return Err(SuDoKuError::DuplicateValue);
+        }
+        self.place(pos, val as usize - 1);
+        Ok(())
+    }
+
+    /// get value, `pos` is `x * 9 + y`.
+    pub fn get(&self, pos: usize) -> Option<u8> {
+        if pos >= 81 || self.solved & 1 << pos == 0 {
+            return None;
+        }
+        (0..9)
+            .find(|&d| self.cand[d] & 1 << pos != 0)
+            .map(|d| d as u8 + 1)
+    }
+
+    /// the values which can still be put on a slot.
+    pub fn candidates(&self, pos: usize) -> CandidateSet {
+        (0..9)
+            .filter(|&d| pos < 81 && self.cand[d] & 1 << pos != 0)
+            .map(|d| d as u8 + 1)
+            .collect()
+    }
+
+    /// check if every slot has a value.
+    pub fn is_solved(&self) -> bool {
+        self.solved == ALL
+    }
+
+    /// convert to a `Board`.
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new();
+        for pos in 0..81 {
+            if let Some(val) = self.get(pos) {
+                board.unchecked_set(pos / 9, pos % 9, val);
+            }
+        }
+        board
+    }
+
+    /// put digit `d` (0-based) on a slot, the slot must allow it.
+    fn place(&mut self, pos: usize, d: usize) {
+        let bit = 1 << pos;
+        for cand in self.cand.iter_mut() {
+            *cand &= !bit;
+        }
+        self.cand[d] = self.cand[d] & !PEERS[pos] | bit;
+        self.solved |= bit;
+    }
+
+    /// place naked and hidden singles until none left.
+    /// return false on a contradiction.
+    fn propagate(&mut self) -> bool {
+        loop {
+            // count the candidates of every slot up to two, bit by bit
+            let (mut once, mut twice) = (0, 0);
+            for &cand in self.cand.iter() {
+                twice |= once & cand;
+                once |= cand;
+            }
+            let open = ALL & !self.solved;
+            if open & !once != 0 {
+                // a slot without candidate
+                return false;
+            }
+            let mut singles = once & !twice & open;
+            if singles != 0 {
+                while singles != 0 {
+                    let pos = lowest(singles);
+                    singles &= singles - 1;
+                    // an earlier single of this round can take the last candidate
+                    match (0..9).find(|&d| self.cand[d] & 1 << pos != 0) {
+                        Some(d) => self.place(pos, d),
+                        None => return false,
+                    }
+                }
+                continue;
+            }
+            let mut progress = false;
+            for d in 0..9 {
+                if self.cand[d] & !self.solved == 0 {
+                    // every place of the digit is taken
+                    continue;
+                }
+                for &unit in UNITS.iter() {
+                    let here = self.cand[d] & unit;
+                    if here & !self.solved == 0 {
+                        if here == 0 {
+                            // a digit with no place in a unit
+                            return false;
+                        }
+                        continue;
+                    }
+                    if here & (here - 1) == 0 {
+                        self.place(lowest(here), d);
+                        progress = true;
+                    }
+                }
+            }
+            if !progress {
+                return true;
+            }
+        }
+    }
+
+    /// the open slot with the fewest candidates.
+    fn branch(&self) -> usize {
+        let (mut once, mut twice, mut thrice) = (0, 0, 0);
+        for &cand in self.cand.iter() {
+            thrice |= twice & cand;
+            twice |= once & cand;
+            once |= cand;
+        }
+        let open = ALL & !self.solved;
+        let pairs = twice & !thrice & open;
+        if pairs != 0 {
+            return lowest(pairs);
+        }
+        let mut best = (10, 0);
+        let mut rest = open;
+        while rest != 0 {
+            let pos = lowest(rest);
+            rest &= rest - 1;
+            let count = self
+                .cand
+                .iter()
+                .filter(|&&cand| cand & 1 << pos != 0)
+                .count();
+            if count < best.0 {
+                best = (count, pos);
+            }
+        }
+        best.1
+    }
+}
+
+impl TryFrom<&Board> for PackedBoard {
+    type Error = SuDoKuError;
+
+    /// only a classic board can be packed.
+    fn try_from(board: &Board) -> Result<Self, Self::Error> {
+        if board.is_jigsaw()
+            || board.is_diagonal()
+            || board.is_killer()
+            || !board.constraints().is_empty()
+        {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        let mut packed = PackedBoard::new();
+        for pos in 0..81 {
+            if let Some(val) = board.unchecked_get(pos / 9, pos % 9) {
+                packed.set(pos, val)?;
+            }
+        }
+        Ok(packed)
+    }
+}
+
+impl From<&PackedBoard> for Board {
+    fn from(packed: &PackedBoard) -> Self {
+        packed.to_board()
+    }
+}
+
+impl core::str::FromStr for PackedBoard {
+    type Err = SuDoKuError;
+
+    /// one char per slot, `1`-`9` for a value and any other char for empty.
+    /// whitespace is skipped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut board = PackedBoard::new();
+        let slots = s.chars().filter(|ch| !ch.is_whitespace()).take(81);
+        for (pos, ch) in slots.enumerate() {
+            if let Some(val @ 1..=9) = ch.to_digit(10) {
+                board.set(pos, val as u8)?;
+            }
+        }
+        Ok(board)
+    }
+}
+
+impl fmt::Display for PackedBoard {
+    /// one line of 81 chars, `.` for empty.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for pos in 0..81 {
+            let ch = match self.get(pos) {
+                Some(val) => (val + b'0') as char,
+                None => '.',
+            };
+            write!(f, "{}", ch)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PackedBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PackedBoard({})", self)
+    }
+}
+
+/// solve packed boards with bitwise singles and a guess on the slot with the fewest candidates.
+#[derive(Default, Clone)]
+pub struct PackedSolver {
+    budget: Budget,
+}
+
+impl PackedSolver {
+    /// return a new solver.
+    pub fn new() -> Self {
+        PackedSolver {
+            budget: Budget::new(),
+        }
+    }
+
+    /// return a new solver which gives up with `SuDoKuError::BudgetExhausted`
+    /// once a search runs out of `budget`. a node is a guess.
+    pub fn with_budget(budget: Budget) -> Self {
+        PackedSolver { budget }
+    }
+
+    /// find a solve.
+    pub fn solve(&self, board: &mut PackedBoard) -> Result<(), SuDoKuError> {
+        let mut first = None;
+        self.search(*board, 1, &mut 0, &mut first, &mut self.budget.meter())?;
+        *board = first.ok_or(SuDoKuError::NotSolveable)?;
+        Ok(())
+    }
+
+    /// check if the solve is unique, an unsolvable board is `SuDoKuError::NotSolveable`.
+    pub fn unique(&self, board: &PackedBoard) -> Result<bool, SuDoKuError> {
+        match self.count(board, 2)? {
+            0 => Err(SuDoKuError::NotSolveable),
+            found => Ok(found == 1),
+        }
+    }
+
+    /// count the solves, stop at `limit`.
+    pub fn count(&self, board: &PackedBoard, limit: usize) -> Result<usize, SuDoKuError> {
+        let mut found = 0;
+        self.search(
+            *board,
+            limit,
+            &mut found,
+            &mut None,
+            &mut self.budget.meter(),
+        )?;
+        Ok(found)
+    }
+
+    /// solve many boards, one result for each.
+    /// ```
+    /// use sudoku_rs::packed::{PackedBoard, PackedSolver};
+    ///
+    /// let mut boards = vec![PackedBoard::new(); 3];
+    /// let res = PackedSolver::new().solve_all(&mut boards);
+    /// assert!(res.iter().all(|res| res.is_ok()));
+    /// assert!(boards.iter().all(|b| b.is_solved()));
+    /// ```
+    pub fn solve_all(&self, boards: &mut [PackedBoard]) -> Vec<Result<(), SuDoKuError>> {
+        boards.iter_mut().map(|board| self.solve(board)).collect()
+    }
+
+    fn search(
+        &self,
+        mut board: PackedBoard,
+        limit: usize,
+        found: &mut usize,
+        first: &mut Option<PackedBoard>,
+        meter: &mut Meter<'_>,
+    ) -> Result<(), SuDoKuError> {
+        if !board.propagate() {
+            return Ok(());
+        }
+        if board.is_solved() {
+            *found += 1;
+            first.get_or_insert(board);
+            return Ok(());
+        }
+        meter.tick()?;
+        let pos = board.branch();
+        for d in 0..9 {
+            if board.cand[d] & 1 << pos == 0 {
+                continue;
+            }
+            let mut next = board;
+            next.place(pos, d);
+            self.search(next, limit, found, first, meter)?;
+            if *found >= limit {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{generator::Generator, solver::DfsSolver};
+
+    const HARD: &str =
+        "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+
+    #[test]
+    fn masks() {
+        assert_eq!(UNITS.iter().map(|unit| unit.count_ones()).sum::<u32>(), 243);
+        assert!(PEERS.iter().all(|peers| peers.count_ones() == 20));
+        assert_eq!(PEERS[0] & 1 << 80, 0);
+        assert_ne!(PEERS[0] & 1 << 20, 0);
+    }
+
+    #[test]
+    fn set_get() {
+        let mut b = PackedBoard::new();
+        b.set(0, 5).unwrap();
+        assert_eq!(b.get(0), Some(5));
+        assert_eq!(b.get(1), None);
+        assert!(!b.candidates(1).contains(5));
+        assert!(b.candidates(80).contains(5));
+        assert!(matches!(b.set(10, 5), Err(SuDoKuError::DuplicateValue)));
+        assert!(matches!(b.set(81, 5), Err(SuDoKuError::OutOfBound)));
+        assert!(matches!(b.set(1, 0), Err(SuDoKuError::InvalidValue)));
+    }
+
+    #[test]
+    fn convert() {
+        let board: Board = HARD.parse().unwrap();
+        let packed = PackedBoard::try_from(&board).unwrap();
+        assert_eq!(packed.to_string().replace('.', "0"), HARD);
+        assert_eq!(Board::from(&packed).to_string(), board.to_string());
+        assert_eq!(HARD.parse::<PackedBoard>().unwrap(), packed);
+        assert!(PackedBoard::try_from(&Board::new_diagonal()).is_err());
+    }
+
+    #[test]
+    fn solve() {
+        let mut packed: PackedBoard = HARD.parse().unwrap();
+        let solver = PackedSolver::new();
+        assert!(solver.unique(&packed).unwrap());
+        solver.solve(&mut packed).unwrap();
+        let mut board: Board = HARD.parse().unwrap();
+        DfsSolver::new().solve(&mut board).unwrap();
+        assert_eq!(packed.to_board().to_string(), board.to_string());
+        assert_eq!(solver.count(&PackedBoard::new(), 5).unwrap(), 5);
+    }
+
+    #[test]
+    fn cross_check() {
+        let mut g = Generator::new(8);
+        for _ in 0..5 {
+            let board = g.generate(&Board::new()).unwrap();
+            let mut packed = PackedBoard::try_from(&board).unwrap();
+            assert!(PackedSolver::new().unique(&packed).unwrap());
+            PackedSolver::new().solve(&mut packed).unwrap();
+            let mut solved = board.clone();
+            DfsSolver::new().solve(&mut solved).unwrap();
+            assert_eq!(packed.to_board().to_string(), solved.to_string());
+        }
+    }
+
+    #[test]
+    fn unsolvable() {
+        let mut b = PackedBoard::new();
+        for &(pos, val) in [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (9, 4),
+            (10, 5),
+            (11, 6),
+            (21, 7),
+            (22, 8),
+        ]
+        .iter()
+        {
+            b.set(pos, val).unwrap();
+        }
+        // slot 20 can only hold 9, and so can slot 23 of the same row
+        b.set(23, 9).unwrap();
+        assert!(matches!(
+            PackedSolver::new().solve(&mut b),
+            Err(SuDoKuError::NotSolveable)
+        ));
+        assert!(matches!(
+            PackedSolver::new().unique(&b),
+            Err(SuDoKuError::NotSolveable)
+        ));
+        let budget = Budget::new().max_nodes(1);
+        assert!(matches!(
+            PackedSolver::with_budget(budget).solve(&mut PackedBoard::new()),
+            Err(SuDoKuError::BudgetExhausted)
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+//! the parts of the std prelude which live in `alloc`.
+pub(crate) use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
@@ -0,0 +1,365 @@
+//! python bindings, built with the `python` feature.
+//!
+//! a board is made from the text format, a flat list of 81 values or 9 rows of 9 values,
+//! 0 for empty. the `*_many` functions work on a batch with the GIL released.
+//! ```python
+//! from sudoku_rs import Board, DfsSolver, Grade, grade_many
+//!
+//! board = Board("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......")
+//! DfsSolver().unique(board)  # True
+//! Grade().grade(board)  # Score(dfs_complexity=..., empty_slot_count=64)
+//! DfsSolver(max_nodes=100_000).solve(board)
+//! board.rows()
+//! scores = grade_many(open("puzzles.txt").read().split())
+//! ```
+//! build a wheel with `maturin build --release`, see `pyproject.toml`.
+use crate::{
+    board::Board, budget::Budget, error::SuDoKuError, grade::Grade, prelude::*, solver::DfsSolver,
+};
+use pyo3::{
+    exceptions::{PyIndexError, PyValueError},
+    prelude::*,
+};
+
+impl From<SuDoKuError> for PyErr {
+    fn from(err: SuDoKuError) -> Self {
+        match err {
+            SuDoKuError::OutOfBound => PyIndexError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// an `IndexError` for a slot out of bound, rather than a panic.
+fn check_bound(x: usize, y: usize) -> PyResult<()> {
+    if x >= 9 || y >= 9 {
+        return Err(SuDoKuError::OutOfBound.into());
+    }
+    Ok(())
+}
+
+fn budget(max_nodes: Option<usize>) -> Budget {
+    match max_nodes {
+        Some(nodes) => Budget::new().max_nodes(nodes),
+        None => Budget::new(),
+    }
+}
+
+/// a board from a `Board`, a text, 81 values or 9 rows.
+fn extract_board(source: &Bound<'_, PyAny>) -> PyResult<Board> {
+    if let Ok(board) = source.extract::<PyRef<'_, PyBoard>>() {
+        return Ok(board.board.clone());
+    }
+    if let Ok(text) = source.extract::<String>() {
+        return Ok(text.parse()?);
+    }
+    let cells: Vec<u8> = match source.extract::<Vec<Vec<u8>>>() {
+        Ok(rows) if rows.len() == 9 && rows.iter().all(|row| row.len() == 9) => rows.concat(),
+        Ok(_) => return Err(SuDoKuError::InvalidFormat.into()),
+        Err(_) => source.extract()?,
+    };
+    if cells.len() != 81 {
+        return Err(SuDoKuError::InvalidFormat.into());
+    }
+    let mut board = Board::new();
+    for (pos, &val) in cells.iter().enumerate() {
+        if val != 0 {
+            board.set(pos / 9, pos % 9, val)?;
+        }
+    }
+    Ok(board)
+}
+
+fn extract_boards(sources: Vec<Bound<'_, PyAny>>) -> PyResult<Vec<Board>> {
+    sources.iter().map(extract_board).collect()
+}
+
+/// a sudoku board.
+#[pyclass(name = "Board", module = "sudoku_rs", skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyBoard {
+    board: Board,
+}
+
+#[pymethods]
+impl PyBoard {
+    /// an empty board, or a board from a text, 81 values or 9 rows.
+    #[new]
+    #[pyo3(signature = (source=None))]
+    fn new(source: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let board = match source {
+            Some(source) => extract_board(source)?,
+            None => Board::new(),
+        };
+        Ok(PyBoard { board })
+    }
+
+    /// the value of a slot, `None` for empty.
+    fn get(&self, x: usize, y: usize) -> PyResult<Option<u8>> {
+        Ok(self.board.get(x, y)?)
+    }
+
+    /// put a value on a slot.
+    fn set(&mut self, x: usize, y: usize, val: u8) -> PyResult<()> {
+        Ok(self.board.set(x, y, val)?)
+    }
+
+    /// clear a slot.
+    fn unset(&mut self, x: usize, y: usize) -> PyResult<()> {
+        check_bound(x, y)?;
+        self.board.unset(x, y);
+        Ok(())
+    }
+
+    /// the values which can be put on a slot.
+    // values are `u32` here, a `Vec<u8>` would be `bytes` in python
+    fn candidates(&self, x: usize, y: usize) -> PyResult<Vec<u32>> {
+        check_bound(x, y)?;
+        Ok(self.board.candidates(x, y).map(u32::from).collect())
+    }
+
+    /// the 81 values, 0 for empty.
+    fn cells(&self) -> Vec<u32> {
+        (0..81)
+            .map(|pos| {
+                self.board
+                    .unchecked_get(pos / 9, pos % 9)
+                    .unwrap_or(0)
+                    .into()
+            })
+            .collect()
+    }
+
+    /// the 9 rows of values, 0 for empty.
+    fn rows(&self) -> Vec<Vec<u32>> {
+        self.cells().chunks(9).map(|row| row.to_vec()).collect()
+    }
+
+    /// check if the board is filled without conflict.
+    fn is_win(&self) -> bool {
+        self.board.is_win()
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Board({:?})", self.board.to_string())
+    }
+}
+
+/// the score of `Grade.grade`.
+#[pyclass(name = "Score", module = "sudoku_rs", get_all, skip_from_py_object)]
+#[derive(Clone, Copy)]
+pub struct PyScore {
+    dfs_complexity: usize,
+    empty_slot_count: usize,
+}
+
+#[pymethods]
+impl PyScore {
+    fn __repr__(&self) -> String {
+        format!(
+            "Score(dfs_complexity={}, empty_slot_count={})",
+            self.dfs_complexity, self.empty_slot_count
+        )
+    }
+}
+
+/// solve in the dfs way, a search visits at most `max_nodes` nodes.
+#[pyclass(name = "DfsSolver", module = "sudoku_rs")]
+pub struct PySolver {
+    max_nodes: Option<usize>,
+}
+
+#[pymethods]
+impl PySolver {
+    #[new]
+    #[pyo3(signature = (max_nodes=None))]
+    fn new(max_nodes: Option<usize>) -> Self {
+        PySolver { max_nodes }
+    }
+
+    /// solve a board in place.
+    fn solve(&self, py: Python<'_>, mut board: PyRefMut<'_, PyBoard>) -> PyResult<()> {
+        let solver = DfsSolver::with_budget(budget(self.max_nodes));
+        let board = &mut board.board;
+        py.detach(|| solver.solve(board))?;
+        Ok(())
+    }
+
+    /// check if the solve of a board is unique, the board is kept as it is.
+    fn unique(&self, py: Python<'_>, board: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let solver = DfsSolver::with_budget(budget(self.max_nodes));
+        let mut board = extract_board(board)?;
+        Ok(py.detach(|| solver.unique(&mut board))?)
+    }
+
+    /// a value to fill next, `(x, y, value)`, `None` if the board is full.
+    fn hint(&self, board: &Bound<'_, PyAny>) -> PyResult<Option<(usize, usize, u8)>> {
+        let solver = DfsSolver::with_budget(budget(self.max_nodes));
+        Ok(solver.hint(&extract_board(board)?)?)
+    }
+}
+
+/// grade in a human-like dfs way.
+#[pyclass(name = "Grade", module = "sudoku_rs")]
+pub struct PyGrade {
+    max_nodes: Option<usize>,
+    max_complexity: Option<usize>,
+}
+
+impl PyGrade {
+    fn grade_one(&self, board: &mut Board) -> Result<PyScore, SuDoKuError> {
+        let mut grade = Grade::with_budget(budget(self.max_nodes));
+        if let Some(max_complexity) = self.max_complexity {
+            grade = grade.max_complexity(max_complexity);
+        }
+        let score = grade.try_grade(board)?;
+        Ok(PyScore {
+            dfs_complexity: score.dfs_complexity,
+            empty_slot_count: score.empty_slot_count,
+        })
+    }
+}
+
+#[pymethods]
+impl PyGrade {
+    #[new]
+    #[pyo3(signature = (max_nodes=None, max_complexity=None))]
+    fn new(max_nodes: Option<usize>, max_complexity: Option<usize>) -> Self {
+        PyGrade {
+            max_nodes,
+            max_complexity,
+        }
+    }
+
+    /// grade a board, the board is kept as it is.
+    fn grade(&self, py: Python<'_>, board: &Bound<'_, PyAny>) -> PyResult<PyScore> {
+        let mut board = extract_board(board)?;
+        Ok(py.detach(|| self.grade_one(&mut board))?)
+    }
+}
+
+/// solve many boards, `None` for a board without solve or out of budget.
+#[pyfunction]
+#[pyo3(signature = (boards, max_nodes=None))]
+fn solve_many(
+    py: Python<'_>,
+    boards: Vec<Bound<'_, PyAny>>,
+    max_nodes: Option<usize>,
+) -> PyResult<Vec<Option<PyBoard>>> {
+    let boards = extract_boards(boards)?;
+    let solver = DfsSolver::with_budget(budget(max_nodes));
+    Ok(py.detach(|| {
+        boards
+            .into_iter()
+            .map(|mut board| solver.solve(&mut board).ok().map(|_| PyBoard { board }))
+            .collect()
+    }))
+}
+
+/// check many boards, `None` for a board out of budget.
+#[pyfunction]
+#[pyo3(signature = (boards, max_nodes=None))]
+fn unique_many(
+    py: Python<'_>,
+    boards: Vec<Bound<'_, PyAny>>,
+    max_nodes: Option<usize>,
+) -> PyResult<Vec<Option<bool>>> {
+    let boards = extract_boards(boards)?;
+    let solver = DfsSolver::with_budget(budget(max_nodes));
+    Ok(py.detach(|| {
+        boards
+            .into_iter()
+            .map(|mut board| solver.unique(&mut board).ok())
+            .collect()
+    }))
+}
+
+/// grade many boards, `None` for a board out of budget.
+#[pyfunction]
+#[pyo3(signature = (boards, max_nodes=None, max_complexity=None))]
+fn grade_many(
+    py: Python<'_>,
+    boards: Vec<Bound<'_, PyAny>>,
+    max_nodes: Option<usize>,
+    max_complexity: Option<usize>,
+) -> PyResult<Vec<Option<PyScore>>> {
+    let boards = extract_boards(boards)?;
+    let grade = PyGrade::new(max_nodes, max_complexity);
+    Ok(py.detach(|| {
+        boards
+            .into_iter()
+            .map(|mut board| grade.grade_one(&mut board).ok())
+            .collect()
+    }))
+}
+
+#[pymodule]
+fn sudoku_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBoard>()?;
+    m.add_class::<PyScore>()?;
+    m.add_class::<PySolver>()?;
+    m.add_class::<PyGrade>()?;
+    m.add_function(wrap_pyfunction!(solve_many, m)?)?;
+    m.add_function(wrap_pyfunction!(unique_many, m)?)?;
+    m.add_function(wrap_pyfunction!(grade_many, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pyo3::types::PyList;
+
+    const EASY: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+
+    #[test]
+    fn board() {
+        Python::initialize();
+        Python::attach(|py| {
+            let text = EASY.into_pyobject(py).unwrap().into_any();
+            let board = extract_board(&text).unwrap();
+            let cells = PyBoard { board }.cells();
+            let list = PyList::new(py, &cells).unwrap().into_any();
+            let from_list = extract_board(&list).unwrap();
+            let rows = PyBoard { board: from_list }.rows();
+            let nested = PyList::new(py, &rows).unwrap().into_any();
+            let board = PyBoard::new(Some(&nested)).unwrap();
+            assert_eq!(board.cells(), cells);
+            assert_eq!(board.get(0, 0).unwrap(), Some(2));
+            let short = PyList::new(py, &cells[..80]).unwrap().into_any();
+            assert!(extract_board(&short).is_err());
+        });
+    }
+
+    #[test]
+    fn batch() {
+        Python::initialize();
+        Python::attach(|py| {
+            let boards = vec![
+                EASY.into_pyobject(py).unwrap().into_any(),
+                "".into_pyobject(py).unwrap().into_any(),
+            ];
+            let solved = solve_many(py, boards.clone(), None).unwrap();
+            assert!(solved.iter().all(|board| board.as_ref().unwrap().is_win()));
+            let unique = unique_many(py, boards.clone(), None).unwrap();
+            assert_eq!(unique, vec![Some(true), Some(false)]);
+            let scores = grade_many(py, boards, Some(10), None).unwrap();
+            assert!(scores.iter().all(|score| score.is_none()));
+            let bad = vec!["variant: nothing".into_pyobject(py).unwrap().into_any()];
+            assert!(solve_many(py, bad, None).is_err());
+        });
+    }
+}
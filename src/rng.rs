@@ -0,0 +1,146 @@
+//! random numbers for everything random in the crate, reproducible from a seed.
+//!
+//! the randomized functions take any `Rng`. `SplitMix64` is built in, so the crate needs
+//! no dependency. with the `rand` feature every `rand_core::RngCore` is a `Rng`.
+//! ```
+//! use sudoku_rs::{board::Board, rng::SplitMix64, solver::DfsSolver};
+//!
+//! let mut a = Board::new();
+//! let mut b = Board::new();
+//! DfsSolver::new().solve_random(&mut a, &mut SplitMix64::new(4)).unwrap();
+//! DfsSolver::new().solve_random(&mut b, &mut SplitMix64::new(4)).unwrap();
+//! assert_eq!(a.to_string(), b.to_string());
+//! ```
+
+/// a source of random numbers.
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    /// a number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        self.below_u64(n as u64) as usize
+    }
+
+    /// a number in `0..n`.
+    fn below_u64(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// fisher-yates shuffle.
+    fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand_core::RngCore + ?Sized> Rng for R {
+    fn next_u64(&mut self) -> u64 {
+        rand_core::RngCore::next_u64(self)
+    }
+}
+
+/// splitmix64 by Sebastiano Vigna.
+/// fast and good enough for shuffling, not for cryptography.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+// with `rand` it is a `Rng` as a `RngCore`
+#[cfg(not(feature = "rand"))]
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        SplitMix64::next_u64(self)
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (SplitMix64::next_u64(self) >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        SplitMix64::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn below() {
+        let mut rng = SplitMix64::new(0);
+        for _ in 0..1000 {
+            assert!(rng.below(9) < 9);
+        }
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut rng = SplitMix64::new(7);
+        let mut v: Vec<u8> = (0..9).collect();
+        rng.shuffle(&mut v);
+        v.sort_unstable();
+        assert_eq!(v, (0..9).collect::<Vec<u8>>());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn rng_core() {
+        /// counts up, a rng only in name.
+        struct Counter(u64);
+
+        impl rand_core::RngCore for Counter {
+            fn next_u32(&mut self) -> u32 {
+                rand_core::RngCore::next_u64(self) as u32
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.0 = self.0.wrapping_add(1 << 60);
+                self.0
+            }
+
+            fn fill_bytes(&mut self, dst: &mut [u8]) {
+                rand_core::impls::fill_bytes_via_next(self, dst)
+            }
+        }
+
+        let mut counter = Counter(0);
+        assert_eq!(counter.below(16), 1);
+        assert_eq!(counter.below(16), 2);
+        let mut a = SplitMix64::new(3);
+        let mut b = SplitMix64::new(3);
+        assert_eq!(rand_core::RngCore::next_u64(&mut a), Rng::next_u64(&mut b));
+    }
+}
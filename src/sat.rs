@@ -0,0 +1,566 @@
+//! encode sudoku as cnf, for the built-in cdcl solver or an external one.
+//!
+//! variable `81 * x + 9 * y + val` (1-based, `val` in 1..=9) is true
+//! when slot (x, y) holds `val`, see `var`.
+//! ```
+//! use sudoku_rs::{board::Board, sat::{Cnf, Encoding, SatSolver}};
+//!
+//! let sudoku_str = "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+//! let mut b: Board = sudoku_str.parse().unwrap();
+//! let cnf = Cnf::encode(&b, Encoding::Minimal).unwrap();
+//! assert!(cnf.to_string().starts_with("p cnf 729 "));
+//! assert!(SatSolver::new().unique(&mut b.clone()).unwrap());
+//! SatSolver::new().solve(&mut b).unwrap();
+//! assert!(b.is_win());
+//! ```
+//! a model from an external solver can be read back.
+//! ```
+//! use sudoku_rs::{board::Board, sat};
+//!
+//! let b = Board::new();
+//! // minisat style output, only the true variables matter
+//! let model: String = (0..81).map(|pos| format!("{} ", pos * 9 + (pos % 9 + pos / 9 * 3 + pos / 27) % 9 + 1)).collect();
+//! let solved = sat::from_model(&b, &format!("SAT\n{}0", model)).unwrap();
+//! assert!(solved.is_win());
+//! ```
+//!
+//! rows, columns, regions, diagonals and cages are encoded exactly.
+//! other constraints are encoded by the pairs of values they forbid,
+//! which is exact for constraints between two slots like anti-knight, kropki or thermometer.
+//! `Cnf::encode` fails on constraints over more slots, like arrow or sandwich.
+//! `SatSolver` still solves them, it blocks models breaking them and solves again.
+use crate::{board::Board, budget::Budget, constraint::Constraint, error::SuDoKuError, prelude::*};
+use core::fmt;
+
+mod cdcl;
+
+use cdcl::Cdcl;
+
+/// the variable of `val` on slot (x, y).
+pub fn var(x: usize, y: usize, val: u8) -> i32 {
+    (x * 81 + y * 9 + val as usize) as i32
+}
+
+/// how many clauses to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// a slot holds at least one value, a value is at most once in a unit.
+    Minimal,
+    /// also a slot holds at most one value, a value is at least once in a unit.
+    /// larger, but propagates better.
+    Extended,
+}
+
+/// a formula in conjunctive normal form, with dimacs style literals.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cnf {
+    vars: usize,
+    clauses: Vec<Vec<i32>>,
+}
+
+impl Cnf {
+    /// return an empty formula of the 729 slot variables.
+    pub fn new() -> Self {
+        Cnf {
+            vars: 729,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// encode a board, values on board become unit clauses.
+    /// fail with `SuDoKuError::InvalidFormat` if a constraint is not pairwise,
+    /// as the models could break it.
+    pub fn encode(board: &Board, encoding: Encoding) -> Result<Self, SuDoKuError> {
+        if !board.constraints().iter().all(|c| c.pairwise()) {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        Ok(Cnf::encode_do(board, encoding))
+    }
+
+    /// encode a board, the constraints which are not pairwise are left to the caller.
+    fn encode_do(board: &Board, encoding: Encoding) -> Self {
+        let mut cnf = Cnf::new();
+        let vals = 1..=9u8;
+        for pos in 0..81 {
+            let (x, y) = (pos / 9, pos % 9);
+            cnf.add_clause(vals.clone().map(|val| var(x, y, val)).collect());
+            if encoding == Encoding::Extended {
+                cnf.at_most_one(&vals.clone().map(|val| var(x, y, val)).collect::<Vec<_>>());
+            }
+            if let Some(val) = board.unchecked_get(x, y) {
+                cnf.add_clause(vec![var(x, y, val)]);
+            }
+        }
+        for unit in units(board) {
+            for val in vals.clone() {
+                let lits: Vec<i32> = unit.iter().map(|&(x, y)| var(x, y, val)).collect();
+                cnf.at_most_one(&lits);
+                if encoding == Encoding::Extended {
+                    cnf.add_clause(lits);
+                }
+            }
+        }
+        for cage in board.cages() {
+            cnf.encode_cage(cage.sum(), cage.slots());
+        }
+        for constraint in board.constraints() {
+            cnf.encode_pairs(constraint.as_ref());
+        }
+        cnf
+    }
+
+    /// the number of variables.
+    pub fn vars(&self) -> usize {
+        self.vars
+    }
+
+    /// the clauses.
+    pub fn clauses(&self) -> &[Vec<i32>] {
+        &self.clauses
+    }
+
+    /// add a new variable, return it.
+    pub fn new_var(&mut self) -> i32 {
+        self.vars += 1;
+        self.vars as i32
+    }
+
+    /// add a clause, at least one of the literals is true.
+    pub fn add_clause(&mut self, clause: Vec<i32>) {
+        for &lit in clause.iter() {
+            self.vars = self.vars.max(lit.unsigned_abs() as usize);
+        }
+        self.clauses.push(clause);
+    }
+
+    /// find a model of the formula, `None` if it is unsatisfiable.
+    /// `model[i]` is the value of variable `i + 1`.
+    pub fn solve(&self, budget: &Budget) -> Result<Option<Vec<bool>>, SuDoKuError> {
+        Cdcl::new(self.vars, &self.clauses).solve(&mut budget.meter())
+    }
+
+    /// pairwise at most one of the literals is true.
+    fn at_most_one(&mut self, lits: &[i32]) {
+        for (i, &a) in lits.iter().enumerate() {
+            for &b in &lits[i + 1..] {
+                self.clauses.push(vec![-a, -b]);
+            }
+        }
+    }
+
+    /// no value repeats in a cage and they sum up to `sum`.
+    /// one extra variable per set of values the cage can hold.
+    fn encode_cage(&mut self, sum: u8, slots: &[(usize, usize)]) {
+        for val in 1..=9 {
+            let lits: Vec<i32> = slots.iter().map(|&(x, y)| var(x, y, val)).collect();
+            self.at_most_one(&lits);
+        }
+        let mut any = Vec::new();
+        // every subset of 1..=9 with the right size and sum
+        for mask in 0u16..512 {
+            let values: Vec<u8> = (1..=9).filter(|v| mask >> (v - 1) & 1 == 1).collect();
+            if values.len() != slots.len() || values.iter().sum::<u8>() != sum {
+                continue;
+            }
+            let set = self.new_var();
+            any.push(set);
+            for val in (1..=9).filter(|v| !values.contains(v)) {
+                for &(x, y) in slots {
+                    self.clauses.push(vec![-set, -var(x, y, val)]);
+                }
+            }
+        }
+        self.clauses.push(any);
+    }
+
+    /// forbid the values and the pairs of values a constraint does not allow.
+    /// the constraint is asked on a board holding only the other value.
+    fn encode_pairs(&mut self, constraint: &dyn Constraint) {
+        let mut probe = Board::new();
+        let mut allowed = [[true; 10]; 81];
+        for (pos, allowed) in allowed.iter_mut().enumerate() {
+            let (x, y) = (pos / 9, pos % 9);
+            for val in 1..=9u8 {
+                if !constraint.allows(&probe, x, y, val) {
+                    allowed[val as usize] = false;
+                    self.clauses.push(vec![-var(x, y, val)]);
+                }
+            }
+        }
+        let mut pairs = Vec::new();
+        for a in 0..81 {
+            let (ax, ay) = (a / 9, a % 9);
+            for va in (1..=9u8).filter(|&va| allowed[a][va as usize]) {
+                probe.unchecked_set(ax, ay, va);
+                for b in (0..81).filter(|&b| b != a) {
+                    let (bx, by) = (b / 9, b % 9);
+                    for vb in (1..=9u8).filter(|&vb| allowed[b][vb as usize]) {
+                        if !constraint.allows(&probe, bx, by, vb) {
+                            let (a, b) = (var(ax, ay, va), var(bx, by, vb));
+                            pairs.push((a.min(b), a.max(b)));
+                        }
+                    }
+                }
+                probe.unset(ax, ay);
+            }
+        }
+        // a pair is usually found from both slots
+        pairs.sort_unstable();
+        pairs.dedup();
+        for (a, b) in pairs {
+            self.clauses.push(vec![-a, -b]);
+        }
+    }
+}
+
+/// the slots of every row, column, region and diagonal.
+fn units(board: &Board) -> Vec<Vec<(usize, usize)>> {
+    let mut units = Vec::new();
+    for i in 0..9 {
+        units.push((0..9).map(|j| (i, j)).collect());
+        units.push((0..9).map(|j| (j, i)).collect());
+    }
+    for region in 0..9 {
+        units.push(
+            (0..81)
+                .map(|pos| (pos / 9, pos % 9))
+                .filter(|&(x, y)| board.region(x, y) == region)
+                .collect(),
+        );
+    }
+    if board.is_diagonal() {
+        units.push((0..9).map(|i| (i, i)).collect());
+        units.push((0..9).map(|i| (i, 8 - i)).collect());
+    }
+    units
+}
+
+impl fmt::Display for Cnf {
+    /// the dimacs format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "p cnf {} {}", self.vars, self.clauses.len())?;
+        for clause in self.clauses.iter() {
+            for lit in clause.iter() {
+                write!(f, "{} ", lit)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for Cnf {
+    type Err = SuDoKuError;
+
+    /// parse the dimacs format, comment lines start with `c`.
+    /// the `p cnf` header comes before any clause and covers every literal.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cnf = Cnf {
+            vars: 0,
+            clauses: Vec::new(),
+        };
+        let mut header = None;
+        let mut clause = Vec::new();
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('c') || line.starts_with('%') {
+                continue;
+            }
+            if let Some(words) = line.strip_prefix('p') {
+                if header.is_some() || !cnf.clauses.is_empty() || !clause.is_empty() {
+                    return Err(SuDoKuError::InvalidFormat);
+                }
+                let words: Vec<&str> = words.split_whitespace().collect();
+                match words.as_slice() {
+                    ["cnf", vars, _] => {
+                        let vars = vars.parse().map_err(|_| SuDoKuError::InvalidFormat)?;
+                        header = Some(vars);
+                        cnf.vars = vars;
+                    }
+                    _ => return Err(SuDoKuError::InvalidFormat),
+                }
+                continue;
+            }
+            for word in line.split_whitespace() {
+                match word
+                    .parse::<i32>()
+                    .map_err(|_| SuDoKuError::InvalidFormat)?
+                {
+                    0 => cnf.add_clause(core::mem::take(&mut clause)),
+                    lit => clause.push(lit),
+                }
+            }
+        }
+        if !clause.is_empty() {
+            cnf.add_clause(clause);
+        }
+        if matches!(header, Some(vars) if vars < cnf.vars) {
+            return Err(SuDoKuError::InvalidFormat);
+        }
+        Ok(cnf)
+    }
+}
+
+/// read a model back into a solved board.
+/// accept the `v 1 -2 ... 0` lines of the competition format and the bare
+/// literals of minisat, status and comment lines are skipped.
+pub fn from_model(board: &Board, model: &str) -> Result<Board, SuDoKuError> {
+    let mut values = [None; 81];
+    for line in model.lines().map(str::trim) {
+        let line = line.strip_prefix('v').unwrap_or(line);
+        if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        for word in line.split_whitespace() {
+            let lit: i32 = word.parse().map_err(|_| SuDoKuError::InvalidFormat)?;
+            if (1..=729).contains(&lit) {
+                let pos = (lit as usize - 1) / 9;
+                let val = ((lit - 1) % 9 + 1) as u8;
+                if values[pos].replace(val).is_some() {
+                    return Err(SuDoKuError::DuplicateValue);
+                }
+            }
+        }
+    }
+    let mut solved = board.clone();
+    for (pos, val) in values.iter().enumerate() {
+        let (x, y) = (pos / 9, pos % 9);
+        match (*val, board.unchecked_get(x, y)) {
+            (None, _) => return Err(SuDoKuError::NotSolveable),
+            (Some(val), Some(given)) if val != given => return Err(SuDoKuError::DuplicateValue),
+            (Some(val), _) => solved.unchecked_set(x, y, val),
+        }
+    }
+    Ok(solved)
+}
+
+/// solve a sudoku with the built-in cdcl solver, a cross-check of `DfsSolver`.
+#[derive(Clone, Debug)]
+pub struct SatSolver {
+    encoding: Encoding,
+    budget: Budget,
+}
+
+impl Default for SatSolver {
+    fn default() -> Self {
+        SatSolver::new()
+    }
+}
+
+impl SatSolver {
+    /// return a new solver of the extended encoding.
+    pub fn new() -> Self {
+        SatSolver {
+            encoding: Encoding::Extended,
+            budget: Budget::new(),
+        }
+    }
+
+    /// use another encoding.
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// give up with `SuDoKuError::BudgetExhausted` once a search runs out of `budget`.
+    /// a node is a decision or a conflict.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// find a solve of sudoku.
+    pub fn solve(&self, board: &mut Board) -> Result<(), SuDoKuError> {
+        let mut cnf = Cnf::encode_do(board, self.encoding);
+        *board = self
+            .solve_do(board, &mut cnf)?
+            .ok_or(SuDoKuError::NotSolveable)?;
+        Ok(())
+    }
+
+    /// check if the solve of a sudoku is unique.
+    /// an unsolvable board is `SuDoKuError::NotSolveable`.
+    pub fn unique(&self, board: &mut Board) -> Result<bool, SuDoKuError> {
+        let mut cnf = Cnf::encode_do(board, self.encoding);
+        let solved = self
+            .solve_do(board, &mut cnf)?
+            .ok_or(SuDoKuError::NotSolveable)?;
+        cnf.add_clause(block(&solved));
+        Ok(self.solve_do(board, &mut cnf)?.is_none())
+    }
+
+    /// solve until a model follows every constraint of the board.
+    fn solve_do(&self, board: &Board, cnf: &mut Cnf) -> Result<Option<Board>, SuDoKuError> {
+        loop {
+            let model = match cnf.solve(&self.budget)? {
+                Some(model) => model,
+                None => return Ok(None),
+            };
+            let mut solved = board.clone();
+            for pos in 0..81 {
+                let (x, y) = (pos / 9, pos % 9);
+                let val = (1..=9)
+                    .find(|&val| model[var(x, y, val) as usize - 1])
+                    .ok_or(SuDoKuError::NotSolveable)?;
+                solved.unchecked_set(x, y, val);
+            }
+            if solved.constraints().iter().all(|c| c.check(&solved)) {
+                return Ok(Some(solved));
+            }
+            cnf.add_clause(block(&broken(board, solved)));
+        }
+    }
+}
+
+/// clear the values of a filled board which are not needed to break a constraint,
+/// so blocking it rules out more than one model.
+fn broken(board: &Board, mut solved: Board) -> Board {
+    for pos in 0..81 {
+        let (x, y) = (pos / 9, pos % 9);
+        let val = match solved.unchecked_get(x, y) {
+            // the givens are always there
+            Some(val) if board.is_empty(x, y) => val,
+            _ => continue,
+        };
+        solved.unset(x, y);
+        if solved.constraints().iter().all(|c| c.check(&solved)) {
+            solved.unchecked_set(x, y, val);
+        }
+    }
+    solved
+}
+
+/// a clause ruling out the values on a board.
+fn block(board: &Board) -> Vec<i32> {
+    (0..81)
+        .filter_map(|pos| {
+            let (x, y) = (pos / 9, pos % 9);
+            board.unchecked_get(x, y).map(|val| -var(x, y, val))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        cage::Cage,
+        constraint::{AntiKnight, Arrow},
+        solver::DfsSolver,
+    };
+
+    const PUZZLE: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+
+    #[test]
+    fn encode() {
+        let board: Board = PUZZLE.parse().unwrap();
+        let minimal = Cnf::encode(&board, Encoding::Minimal).unwrap();
+        let extended = Cnf::encode(&board, Encoding::Extended).unwrap();
+        assert_eq!(minimal.vars(), 729);
+        // 81 slots, 27 givens, 27 units * 9 values * 36 pairs
+        assert_eq!(minimal.clauses().len(), 81 + 27 + 27 * 9 * 36);
+        assert_eq!(
+            extended.clauses().len(),
+            minimal.clauses().len() + 81 * 36 + 27 * 9
+        );
+    }
+
+    #[test]
+    fn dimacs_round_trip() {
+        let board: Board = PUZZLE.parse().unwrap();
+        let cnf = Cnf::encode(&board, Encoding::Extended).unwrap();
+        let text = cnf.to_string();
+        assert_eq!(text.parse::<Cnf>().unwrap(), cnf);
+        let cnf: Cnf = "c a comment\np cnf 3 2\n1 -2\n0 3 0\n".parse().unwrap();
+        assert_eq!(cnf.vars(), 3);
+        assert_eq!(cnf.clauses(), &[vec![1, -2], vec![3]]);
+        assert!("p dnf 3 2".parse::<Cnf>().is_err());
+        assert!("1 5 0\np cnf 1 1\n".parse::<Cnf>().is_err());
+        assert!("p cnf 1 1\n1 5 0\n".parse::<Cnf>().is_err());
+        assert!("p cnf 5 1\np cnf 5 1\n1 5 0\n".parse::<Cnf>().is_err());
+    }
+
+    #[test]
+    fn model() {
+        let board: Board = PUZZLE.parse().unwrap();
+        let model = Cnf::encode(&board, Encoding::Minimal)
+            .unwrap()
+            .solve(&Budget::new())
+            .unwrap()
+            .unwrap();
+        let text: Vec<String> = model
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| if val { i as i32 + 1 } else { -(i as i32 + 1) })
+            .map(|lit| lit.to_string())
+            .collect();
+        let solved = from_model(&board, &format!("s SATISFIABLE\nv {} 0", text.join(" "))).unwrap();
+        let mut expect = board.clone();
+        DfsSolver::new().solve(&mut expect).unwrap();
+        assert_eq!(solved.to_string(), expect.to_string());
+        assert!(from_model(&board, "v 1 0").is_err());
+    }
+
+    #[test]
+    fn cross_check() {
+        let board: Board = PUZZLE.parse().unwrap();
+        for encoding in [Encoding::Minimal, Encoding::Extended].iter() {
+            let solver = SatSolver::new().encoding(*encoding);
+            let mut sat = board.clone();
+            solver.solve(&mut sat).unwrap();
+            let mut dfs = board.clone();
+            DfsSolver::new().solve(&mut dfs).unwrap();
+            assert_eq!(sat.to_string(), dfs.to_string());
+            assert!(solver.unique(&mut board.clone()).unwrap());
+        }
+        assert!(!SatSolver::new().unique(&mut Board::new()).unwrap());
+    }
+
+    #[test]
+    fn variants() {
+        let mut board = Board::new_diagonal();
+        board.add_constraint(AntiKnight);
+        SatSolver::new().solve(&mut board).unwrap();
+        assert!(board.is_win());
+        assert!(AntiKnight.check(&board));
+
+        let mut board = Board::new();
+        board
+            .add_cage(Cage::new(3, vec![(0, 0), (0, 1)]).unwrap())
+            .unwrap();
+        board.add_constraint(Arrow::new((4, 4), vec![(3, 3), (2, 2), (1, 1)]).unwrap());
+        SatSolver::new().solve(&mut board).unwrap();
+        assert!(board.is_win());
+        assert!(board.constraints()[0].check(&board));
+        // the arrow is not pairwise
+        assert!(matches!(
+            Cnf::encode(&board, Encoding::Minimal),
+            Err(SuDoKuError::InvalidFormat)
+        ));
+        let mut board = Board::new_diagonal();
+        board.add_constraint(AntiKnight);
+        assert!(Cnf::encode(&board, Encoding::Minimal).is_ok());
+    }
+
+    #[test]
+    fn unsolvable() {
+        let mut board = Board::new();
+        board.set(0, 0, 1).unwrap();
+        board.unchecked_set(0, 1, 1);
+        assert!(matches!(
+            SatSolver::new().solve(&mut board),
+            Err(SuDoKuError::NotSolveable)
+        ));
+        assert!(matches!(
+            SatSolver::new().unique(&mut board),
+            Err(SuDoKuError::NotSolveable)
+        ));
+        let budget = Budget::new().max_nodes(3);
+        assert!(matches!(
+            SatSolver::new()
+                .with_budget(budget)
+                .solve(&mut Board::new()),
+            Err(SuDoKuError::BudgetExhausted)
+        ));
+    }
+}
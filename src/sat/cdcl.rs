@@ -0,0 +1,344 @@
+//! a small cdcl sat solver.
+//!
+//! two watched literals, first uip learning with backjumping,
+//! vsids-like activity and phase saving. no restart and no clause deletion,
+//! which is fine for formulas of sudoku size.
+use crate::{budget::Meter, error::SuDoKuError, prelude::*};
+
+/// a literal, `2 * var` for positive and `2 * var + 1` for negative.
+type Lit = usize;
+
+fn lit(dimacs: i32) -> Lit {
+    let var = dimacs.unsigned_abs() as usize - 1;
+    var * 2 + (dimacs < 0) as usize
+}
+
+fn var(lit: Lit) -> usize {
+    lit / 2
+}
+
+fn neg(lit: Lit) -> Lit {
+    lit ^ 1
+}
+
+pub(crate) struct Cdcl {
+    clauses: Vec<Vec<Lit>>,
+    /// clauses watching a literal, visited when the literal becomes false.
+    watches: Vec<Vec<usize>>,
+    assign: Vec<Option<bool>>,
+    level: Vec<usize>,
+    reason: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    /// where each decision level starts in the trail.
+    trail_lim: Vec<usize>,
+    qhead: usize,
+    activity: Vec<f64>,
+    inc: f64,
+    phase: Vec<bool>,
+    /// an empty clause or conflicting units was found.
+    conflict: bool,
+}
+
+impl Cdcl {
+    /// return a new solver of `vars` variables and dimacs style clauses.
+    pub fn new(vars: usize, clauses: &[Vec<i32>]) -> Self {
+        let mut cdcl = Cdcl {
+            clauses: Vec::with_capacity(clauses.len()),
+            watches: vec![Vec::new(); vars * 2],
+            assign: vec![None; vars],
+            level: vec![0; vars],
+            reason: vec![None; vars],
+            trail: Vec::with_capacity(vars),
+            trail_lim: Vec::new(),
+            qhead: 0,
+            activity: vec![0.0; vars],
+            inc: 1.0,
+            phase: vec![false; vars],
+            conflict: false,
+        };
+        for clause in clauses {
+            let mut lits: Vec<Lit> = clause.iter().map(|&l| lit(l)).collect();
+            lits.sort_unstable();
+            lits.dedup();
+            // a tautology is always true
+            if lits.windows(2).any(|w| w[0] == neg(w[1])) {
+                continue;
+            }
+            cdcl.add_clause(lits);
+        }
+        cdcl
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.assign[var(lit)].map(|val| val != (lit & 1 == 1))
+    }
+
+    fn add_clause(&mut self, lits: Vec<Lit>) {
+        match lits.len() {
+            0 => self.conflict = true,
+            1 => match self.value(lits[0]) {
+                Some(true) => {}
+                Some(false) => self.conflict = true,
+                None => self.enqueue(lits[0], None),
+            },
+            _ => {
+                let id = self.clauses.len();
+                self.watches[lits[0]].push(id);
+                self.watches[lits[1]].push(id);
+                self.clauses.push(lits);
+            }
+        }
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let v = var(lit);
+        self.assign[v] = Some(lit & 1 == 0);
+        self.level[v] = self.trail_lim.len();
+        self.reason[v] = reason;
+        self.trail.push(lit);
+    }
+
+    /// unit propagation, return a conflicting clause.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.qhead < self.trail.len() {
+            let false_lit = neg(self.trail[self.qhead]);
+            self.qhead += 1;
+            let mut watches = core::mem::take(&mut self.watches[false_lit]);
+            let mut keep = 0;
+            let mut conflict = None;
+            let mut i = 0;
+            while i < watches.len() {
+                let id = watches[i];
+                i += 1;
+                let clause = &mut self.clauses[id];
+                // keep the false literal at index 1
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.assign[var(first)].map(|val| val != (first & 1 == 1)) == Some(true) {
+                    watches[keep] = id;
+                    keep += 1;
+                    continue;
+                }
+                // look for a new literal to watch
+                let assign = &self.assign;
+                let free = (2..clause.len()).find(|&k| {
+                    let l = clause[k];
+                    assign[var(l)].map(|val| val != (l & 1 == 1)) != Some(false)
+                });
+                if let Some(k) = free {
+                    clause.swap(1, k);
+                    let watch = clause[1];
+                    self.watches[watch].push(id);
+                    continue;
+                }
+                watches[keep] = id;
+                keep += 1;
+                match self.value(first) {
+                    Some(false) => {
+                        conflict = Some(id);
+                        break;
+                    }
+                    _ => self.enqueue(first, Some(id)),
+                }
+            }
+            // the watches not visited because of a conflict
+            while i < watches.len() {
+                watches[keep] = watches[i];
+                keep += 1;
+                i += 1;
+            }
+            watches.truncate(keep);
+            self.watches[false_lit] = watches;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn bump(&mut self, v: usize) {
+        self.activity[v] += self.inc;
+        if self.activity[v] > 1e100 {
+            self.activity.iter_mut().for_each(|a| *a *= 1e-100);
+            self.inc *= 1e-100;
+        }
+    }
+
+    /// learn a first uip clause, return it with the level to backjump to.
+    fn analyze(&mut self, mut conflict: usize) -> (Vec<Lit>, usize) {
+        let current = self.trail_lim.len();
+        let mut seen = vec![false; self.assign.len()];
+        // the asserting literal is put at index 0 at the end
+        let mut learnt = vec![0];
+        let mut counter = 0;
+        let mut p: Option<Lit> = None;
+        let mut index = self.trail.len();
+        loop {
+            // a reason clause has the implied literal at index 0
+            let skip = if p.is_some() { 1 } else { 0 };
+            for k in skip..self.clauses[conflict].len() {
+                let q = self.clauses[conflict][k];
+                let v = var(q);
+                if !seen[v] && self.level[v] > 0 {
+                    seen[v] = true;
+                    self.bump(v);
+                    if self.level[v] == current {
+                        counter += 1;
+                    } else {
+                        learnt.push(q);
+                    }
+                }
+            }
+            loop {
+                index -= 1;
+                if seen[var(self.trail[index])] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            seen[var(lit)] = false;
+            counter -= 1;
+            p = Some(lit);
+            if counter == 0 {
+                break;
+            }
+            conflict = self.reason[var(lit)].expect("analyze: implied literal has a reason");
+        }
+        learnt[0] = neg(p.expect("analyze: conflict has a literal"));
+        let mut back = 0;
+        for k in 1..learnt.len() {
+            let level = self.level[var(learnt[k])];
+            if level > back {
+                back = level;
+                learnt.swap(1, k);
+            }
+        }
+        self.inc /= 0.95;
+        (learnt, back)
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.trail_lim.len() <= level {
+            return;
+        }
+        for &lit in &self.trail[self.trail_lim[level]..] {
+            let v = var(lit);
+            self.phase[v] = lit & 1 == 0;
+            self.assign[v] = None;
+            self.reason[v] = None;
+        }
+        self.trail.truncate(self.trail_lim[level]);
+        self.trail_lim.truncate(level);
+        self.qhead = self.trail.len();
+    }
+
+    /// the unassigned variable with the highest activity.
+    fn pick(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for v in 0..self.assign.len() {
+            if self.assign[v].is_none()
+                && best.map_or(true, |b| self.activity[v] > self.activity[b])
+            {
+                best = Some(v);
+            }
+        }
+        best
+    }
+
+    /// find a model, `None` if the clauses are unsatisfiable.
+    pub fn solve(&mut self, meter: &mut Meter<'_>) -> Result<Option<Vec<bool>>, SuDoKuError> {
+        if self.conflict {
+            return Ok(None);
+        }
+        loop {
+            if let Some(conflict) = self.propagate() {
+                meter.tick()?;
+                if self.trail_lim.is_empty() {
+                    return Ok(None);
+                }
+                let (learnt, back) = self.analyze(conflict);
+                self.backtrack(back);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let id = self.clauses.len();
+                    self.watches[learnt[0]].push(id);
+                    self.watches[learnt[1]].push(id);
+                    self.clauses.push(learnt);
+                    self.enqueue(asserting, Some(id));
+                }
+            } else {
+                let v = match self.pick() {
+                    Some(v) => v,
+                    None => {
+                        let model = self.assign.iter().map(|val| *val == Some(true)).collect();
+                        return Ok(Some(model));
+                    }
+                };
+                meter.tick()?;
+                self.trail_lim.push(self.trail.len());
+                let lit = v * 2 + (!self.phase[v]) as usize;
+                self.enqueue(lit, None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::budget::Budget;
+
+    fn solve(vars: usize, clauses: &[Vec<i32>]) -> Option<Vec<bool>> {
+        Cdcl::new(vars, clauses)
+            .solve(&mut Budget::new().meter())
+            .unwrap()
+    }
+
+    /// clauses saying `n + 1` pigeons sit in `n` holes.
+    fn pigeon(n: usize) -> (usize, Vec<Vec<i32>>) {
+        let var = |p: usize, h: usize| (p * n + h + 1) as i32;
+        let mut clauses = Vec::new();
+        for p in 0..=n {
+            clauses.push((0..n).map(|h| var(p, h)).collect());
+        }
+        for h in 0..n {
+            for p in 0..=n {
+                for q in p + 1..=n {
+                    clauses.push(vec![-var(p, h), -var(q, h)]);
+                }
+            }
+        }
+        ((n + 1) * n, clauses)
+    }
+
+    #[test]
+    fn sat() {
+        let clauses = vec![vec![1, 2], vec![-1, 3], vec![-2, -3], vec![-3, 2, 1]];
+        let model = solve(3, &clauses).unwrap();
+        for clause in clauses {
+            assert!(clause
+                .iter()
+                .any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0)));
+        }
+    }
+
+    #[test]
+    fn unsat() {
+        assert!(solve(1, &[vec![1], vec![-1]]).is_none());
+        assert!(solve(1, &[vec![]]).is_none());
+        let (vars, clauses) = pigeon(5);
+        assert!(solve(vars, &clauses).is_none());
+    }
+
+    #[test]
+    fn budget() {
+        let (vars, clauses) = pigeon(6);
+        let budget = Budget::new().max_nodes(10);
+        let res = Cdcl::new(vars, &clauses).solve(&mut budget.meter());
+        assert!(matches!(res, Err(SuDoKuError::BudgetExhausted)));
+    }
+}
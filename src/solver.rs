@@ -19,76 +19,526 @@
 //! let solver = DfsSolver::new();
 //! assert_eq!(solver.unique(&mut board).unwrap(), false);
 //! ```
-use crate::{board::Board, error::SuDoKuError};
+use crate::{
+    bitset::CandidateSet,
+    board::Board,
+    budget::{Budget, Meter},
+    error::SuDoKuError,
+    multi::MultiBoard,
+    prelude::*,
+    rng::Rng,
+    trace::Observer,
+};
 
 type Slot = (usize, usize, usize);
 
 /// solve a sudoku in the dfs way
-#[derive(Default, Clone, Copy)]
-pub struct DfsSolver;
+/// it holds a budget, so it is not `Copy` since 0.3, clone it instead.
+#[derive(Default, Clone)]
+pub struct DfsSolver {
+    budget: Budget,
+}
 
 impl DfsSolver {
     /// return a new solver
     pub fn new() -> DfsSolver {
-        DfsSolver {}
+        DfsSolver {
+            budget: Budget::new(),
+        }
+    }
+
+    /// return a new solver which gives up with `SuDoKuError::BudgetExhausted`
+    /// once a search runs out of `budget`.
+    pub fn with_budget(budget: Budget) -> DfsSolver {
+        DfsSolver { budget }
     }
 
     /// check if the solve of a sudoku is unique
-    pub fn unique(self, board: &mut Board) -> Result<bool, SuDoKuError> {
-        let path = Vec::with_capacity(81);
-        let path = self.solve_do(board, path)?;
-        Ok(self.solve_do(board, path).is_err())
+    ///
+    /// a full board has nothing to fill, so it is unique.
+    /// before 0.3 this returned `false` for it.
+    pub fn unique(&self, board: &mut Board) -> Result<bool, SuDoKuError> {
+        self.unique_with(board, &mut ())
+    }
+
+    /// check if the solve of a sudoku is unique, report the search to observer.
+    pub fn unique_with<O: Observer>(
+        &self,
+        board: &mut Board,
+        observer: &mut O,
+    ) -> Result<bool, SuDoKuError> {
+        observer.start();
+        let res = self.unique_do(board, observer);
+        observer.finish();
+        res
     }
 
     /// find a solve of sudoku in dfs way
-    pub fn solve(self, board: &mut Board) -> Result<Vec<Slot>, SuDoKuError> {
-        let path = Vec::with_capacity(81);
-        self.solve_do(board, path)
-    }
-
-    fn solve_do(self, board: &mut Board, mut queue: Vec<Slot>) -> Result<Vec<Slot>, SuDoKuError> {
-        let mut cur = if queue.is_empty() {
-            for x in 0..9 {
-                for y in 0..9 {
-                    if board.is_empty(x, y) {
-                        // (x, y, avaliable_count)
-                        queue.push((x, y, board.avaliable_count(x, y)));
-                    }
-                }
-            }
-            // sort by avaliable count
-            queue.sort_unstable_by(|a, b| a.2.cmp(&b.2));
-            // avaliable count no use now
-            queue.iter_mut().for_each(|item| item.2 = 0);
+    ///
+    /// the path is the empty slots in the order they were filled, as `(x, y, mask)`.
+    /// bit `v` of `mask` is set for each value `v` not tried yet, bit 0 marks the slot as seen.
+    /// before 0.3 the third element was the index of the next value to try.
+    pub fn solve(&self, board: &mut Board) -> Result<Vec<Slot>, SuDoKuError> {
+        self.solve_with(board, &mut ())
+    }
+
+    /// find a solve of sudoku in dfs way, report the search to observer.
+    /// ```
+    /// use sudoku_rs::{board::Board, solver::DfsSolver, trace::SolveStats};
+    ///
+    /// let mut board = Board::new();
+    /// let mut stats = SolveStats::new();
+    /// DfsSolver::new().solve_with(&mut board, &mut stats).unwrap();
+    /// assert_eq!(stats.max_depth, 81);
+    /// ```
+    pub fn solve_with<O: Observer>(
+        &self,
+        board: &mut Board,
+        observer: &mut O,
+    ) -> Result<Vec<Slot>, SuDoKuError> {
+        observer.start();
+        let res = self.solve_do(board, Vec::new(), observer, &mut self.budget.meter());
+        observer.finish();
+        res
+    }
+
+    /// find a solve of sudoku in dfs way, trying the values of every slot in a random order.
+    /// the same `rng` state gives the same solve.
+    /// ```
+    /// use sudoku_rs::{board::Board, rng::SplitMix64, solver::DfsSolver};
+    ///
+    /// let mut board = Board::new();
+    /// DfsSolver::new().solve_random(&mut board, &mut SplitMix64::new(1)).unwrap();
+    /// assert!(board.is_win());
+    /// ```
+    pub fn solve_random<R: Rng>(
+        &self,
+        board: &mut Board,
+        rng: &mut R,
+    ) -> Result<Vec<Slot>, SuDoKuError> {
+        let mut queue = queue(board);
+        let order = &mut RandomOrder(rng);
+        self.search(
+            board,
+            &mut queue,
+            0,
+            &mut (),
+            order,
+            &mut self.budget.meter(),
+        )?;
+        Ok(queue)
+    }
+
+    /// a value to fill next, the empty slot with the fewest avaliable values
+    /// and its value in a solve. `None` if the board is full.
+    /// ```
+    /// use sudoku_rs::{board::Board, solver::DfsSolver};
+    ///
+    /// let mut board = Board::new();
+    /// board.set(0, 0, 1).unwrap();
+    /// let (x, y, val) = DfsSolver::new().hint(&board).unwrap().unwrap();
+    /// assert!(board.avaliable_val(x, y).contains(&val));
+    /// ```
+    pub fn hint(&self, board: &Board) -> Result<Option<(usize, usize, u8)>, SuDoKuError> {
+        let slot = (0..81)
+            .map(|pos| (pos / 9, pos % 9))
+            .filter(|&(x, y)| board.is_empty(x, y))
+            .min_by_key(|&(x, y)| board.avaliable_count(x, y));
+        let (x, y) = match slot {
+            Some(slot) => slot,
+            None => return Ok(None),
+        };
+        let mut solved = board.clone();
+        self.solve(&mut solved)?;
+        Ok(solved.unchecked_get(x, y).map(|val| (x, y, val)))
+    }
+
+    /// every solve of a board, one by one in the dfs order.
+    /// the search goes on from the last solve, the budget is for each solve.
+    /// ```
+    /// use sudoku_rs::{board::Board, solver::DfsSolver};
+    ///
+    /// let grids: Vec<Board> = DfsSolver::new()
+    ///     .solutions(&Board::new())
+    ///     .take(3)
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert!(grids.iter().all(|grid| grid.is_win()));
+    /// assert_ne!(grids[0].to_string(), grids[1].to_string());
+    /// assert_ne!(grids[1].to_string(), grids[2].to_string());
+    /// ```
+    pub fn solutions(&self, board: &Board) -> Solutions {
+        Solutions {
+            search: Search::new(self.clone(), board.clone()),
+        }
+    }
+
+    /// find a solve of a multi-grid puzzle in dfs way.
+    pub fn solve_multi(&self, board: &mut MultiBoard) -> Result<(), SuDoKuError> {
+        self.solve_do(board, Vec::new(), &mut (), &mut self.budget.meter())
+            .map(|_| ())
+    }
+
+    /// check if the solve of a multi-grid puzzle is unique.
+    pub fn unique_multi(&self, board: &mut MultiBoard) -> Result<bool, SuDoKuError> {
+        self.unique_do(board, &mut ())
+    }
+
+    /// find a solve of a board, the search state is a fixed array on the stack.
+    /// a classic board is solved without any allocation,
+    /// but constraints may allocate when they check a value.
+    /// ```
+    /// use sudoku_rs::{board::Board, solver::DfsSolver};
+    ///
+    /// let mut board = Board::new();
+    /// DfsSolver::new().solve_fixed(&mut board).unwrap();
+    /// assert!(board.is_win());
+    /// ```
+    pub fn solve_fixed(&self, board: &mut Board) -> Result<(), SuDoKuError> {
+        let mut queue = [(0, 0, 0); 81];
+        let len = fixed_queue(board, &mut queue);
+        self.search(
+            board,
+            &mut queue[..len],
+            0,
+            &mut (),
+            &mut (),
+            &mut self.budget.meter(),
+        )
+    }
+
+    /// check if the solve of a board is unique like `solve_fixed`,
+    /// a classic board is checked without any allocation.
+    /// ```
+    /// use sudoku_rs::{board::Board, solver::DfsSolver};
+    ///
+    /// assert!(!DfsSolver::new().unique_fixed(&mut Board::new()).unwrap());
+    /// ```
+    pub fn unique_fixed(&self, board: &mut Board) -> Result<bool, SuDoKuError> {
+        let mut queue = [(0, 0, 0); 81];
+        let len = fixed_queue(board, &mut queue);
+        self.unique_search(board, &mut queue[..len], &mut ())
+    }
+
+    fn unique_do<P: Puzzle, O: Observer>(
+        &self,
+        board: &mut P,
+        observer: &mut O,
+    ) -> Result<bool, SuDoKuError> {
+        let mut queue = queue(board);
+        self.unique_search(board, &mut queue, observer)
+    }
+
+    fn unique_search<P: Puzzle, O: Observer>(
+        &self,
+        board: &mut P,
+        queue: &mut [Slot],
+        observer: &mut O,
+    ) -> Result<bool, SuDoKuError> {
+        let mut meter = self.budget.meter();
+        self.search(board, queue, 0, observer, &mut (), &mut meter)?;
+        if queue.is_empty() {
+            // nothing to fill, so nothing to go on with
+            return Ok(true);
+        }
+        // go on from the last slot of the first solve
+        match self.search(board, queue, queue.len() - 1, observer, &mut (), &mut meter) {
+            Ok(_) => Ok(false),
+            Err(SuDoKuError::NotSolveable) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn solve_do<P: Puzzle, O: Observer>(
+        &self,
+        board: &mut P,
+        mut queue: Vec<Slot>,
+        observer: &mut O,
+        meter: &mut Meter<'_>,
+    ) -> Result<Vec<Slot>, SuDoKuError> {
+        let cur = if queue.is_empty() {
+            queue = self::queue(board);
             0
         } else {
             queue.len() - 1
         };
-        // (x, y, last_ind)
-        while let Some((xr, yr, ind)) = queue.get_mut(cur) {
+        self.search(board, &mut queue, cur, observer, &mut (), meter)?;
+        Ok(queue)
+    }
+
+    /// fill the slots of `queue` from `cur` on.
+    fn search<P: Puzzle, O: Observer, C: Order>(
+        &self,
+        board: &mut P,
+        queue: &mut [Slot],
+        mut cur: usize,
+        observer: &mut O,
+        order: &mut C,
+        meter: &mut Meter<'_>,
+    ) -> Result<(), SuDoKuError> {
+        // (x, y, remaining candidates)
+        // bit 0 is never a value, it marks the candidates of a slot are computed
+        while let Some((xr, yr, mask)) = queue.get_mut(cur) {
             let x = *xr;
             let y = *yr;
-            if let Some(upper_than_now) = board.avaliable_val(x, y).get(*ind) {
+            meter.tick()?;
+            let forced = if *mask == 0 {
+                // rows, columns, regions, cages and constraints of the board
+                let candidates = board.candidates(x, y);
+                *mask = candidates.bits() as usize | 1;
+                candidates.count() == 1
+            } else {
+                false
+            };
+            if *mask != 1 {
+                let val = order.next(*mask & !1);
+                *mask &= !(1 << val);
                 cur += 1;
-                *ind += 1;
-                board.unchecked_set(x, y, *upper_than_now);
+                board.place(x, y, val);
+                if forced {
+                    observer.place(x, y, val, cur);
+                } else {
+                    observer.guess(x, y, val, cur);
+                }
+                if !board.feasible(x, y) {
+                    // the cage can not be completed, try the next value
+                    cur -= 1;
+                }
             } else if cur != 0 {
                 // no avaliable value
+                observer.backtrack(x, y, cur);
                 cur -= 1;
-                board.unset(x, y);
-                *ind = 0;
+                board.clear(x, y);
+                *mask = 0;
             } else {
                 // no avaliable slot
+                observer.backtrack(x, y, cur);
                 return Err(SuDoKuError::NotSolveable);
             }
         }
-        Ok(queue)
+        Ok(())
+    }
+}
+
+/// a dfs which goes on after a solve, to the next one.
+pub(crate) struct Search<P> {
+    solver: DfsSolver,
+    puzzle: P,
+    queue: Vec<Slot>,
+    started: bool,
+    done: bool,
+}
+
+impl<P: Puzzle> Search<P> {
+    pub fn new(solver: DfsSolver, puzzle: P) -> Self {
+        Search {
+            solver,
+            puzzle,
+            queue: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    /// the puzzle, solved after `step` gives true.
+    pub fn puzzle(&self) -> &P {
+        &self.puzzle
+    }
+
+    /// find the next solve, false once there is no more.
+    pub fn step(&mut self) -> Result<bool, SuDoKuError> {
+        if self.done {
+            return Ok(false);
+        }
+        let cur = if !self.started {
+            self.started = true;
+            self.queue = queue(&self.puzzle);
+            0
+        } else if self.queue.is_empty() {
+            // a full puzzle has one solve, itself
+            self.done = true;
+            return Ok(false);
+        } else {
+            // go on from the last slot of the last solve
+            self.queue.len() - 1
+        };
+        let meter = &mut self.solver.budget.meter();
+        match self.solver.search(
+            &mut self.puzzle,
+            &mut self.queue,
+            cur,
+            &mut (),
+            &mut (),
+            meter,
+        ) {
+            Ok(()) => Ok(true),
+            Err(SuDoKuError::NotSolveable) => {
+                self.done = true;
+                Ok(false)
+            }
+            Err(e) => {
+                self.done = true;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// the solves of a board, see `DfsSolver::solutions`.
+/// an error ends the iteration.
+pub struct Solutions {
+    search: Search<Board>,
+}
+
+impl Iterator for Solutions {
+    type Item = Result<Board, SuDoKuError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.search.step() {
+            Ok(true) => Some(Ok(self.search.puzzle().clone())),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// the order to try the values of a slot in.
+pub(crate) trait Order {
+    /// the next value to try of `mask`, the values left as bits.
+    fn next(&mut self, mask: usize) -> u8;
+}
+
+/// the smallest value first.
+impl Order for () {
+    fn next(&mut self, mask: usize) -> u8 {
+        mask.trailing_zeros() as u8
+    }
+}
+
+/// any value left as likely.
+struct RandomOrder<'a, R>(&'a mut R);
+
+impl<R: Rng> Order for RandomOrder<'_, R> {
+    fn next(&mut self, mut mask: usize) -> u8 {
+        for _ in 0..self.0.below(mask.count_ones() as usize) {
+            mask &= mask - 1;
+        }
+        mask.trailing_zeros() as u8
+    }
+}
+
+/// the empty slots of a puzzle in the order to fill.
+fn queue<P: Puzzle>(board: &P) -> Vec<Slot> {
+    // (x, y, avaliable_count)
+    let mut queue: Vec<Slot> = board
+        .empty_slots()
+        .into_iter()
+        .map(|(x, y)| (x, y, board.avaliable_count(x, y)))
+        .collect();
+    // sort by avaliable count, keep the order of puzzle for same count
+    queue.sort_by_key(|item| item.2);
+    // avaliable count no use now
+    queue.iter_mut().for_each(|item| item.2 = 0);
+    queue
+}
+
+/// put the empty slots of a board in `queue` like `queue`, return how many.
+fn fixed_queue(board: &Board, queue: &mut [Slot; 81]) -> usize {
+    let mut len = 0;
+    for pos in 0..81 {
+        let (x, y) = (pos / 9, pos % 9);
+        if board.is_empty(x, y) {
+            queue[len] = (x, y, Board::avaliable_count(board, x, y));
+            len += 1;
+        }
+    }
+    // the slots are in order, so an unstable sort with them in the key keeps it
+    queue[..len].sort_unstable_by_key(|&(x, y, count)| (count, x, y));
+    queue[..len].iter_mut().for_each(|item| item.2 = 0);
+    len
+}
+
+/// what the dfs needs from a puzzle.
+/// a slot is addressed by two numbers, `(x, y)` for `Board`.
+pub(crate) trait Puzzle {
+    /// the empty slots, in the order to fill when avaliable counts are the same.
+    fn empty_slots(&self) -> Vec<(usize, usize)>;
+
+    /// the values which can be put on a slot.
+    fn candidates(&self, x: usize, y: usize) -> CandidateSet;
+
+    fn avaliable_count(&self, x: usize, y: usize) -> usize;
+
+    fn place(&mut self, x: usize, y: usize, val: u8);
+
+    fn clear(&mut self, x: usize, y: usize);
+
+    /// check the puzzle can still be completed after a placement.
+    fn feasible(&self, x: usize, y: usize) -> bool;
+}
+
+impl Puzzle for Board {
+    fn empty_slots(&self) -> Vec<(usize, usize)> {
+        (0..81)
+            .map(|pos| (pos / 9, pos % 9))
+            .filter(|&(x, y)| self.is_empty(x, y))
+            .collect()
+    }
+
+    fn candidates(&self, x: usize, y: usize) -> CandidateSet {
+        self.blocked(x, y).complement()
+    }
+
+    fn avaliable_count(&self, x: usize, y: usize) -> usize {
+        Board::avaliable_count(self, x, y)
+    }
+
+    fn place(&mut self, x: usize, y: usize, val: u8) {
+        self.unchecked_set(x, y, val);
+    }
+
+    fn clear(&mut self, x: usize, y: usize) {
+        self.unset(x, y);
+    }
+
+    fn feasible(&self, x: usize, y: usize) -> bool {
+        self.cage_feasible(x, y)
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
     use super::*;
+    use crate::rng::SplitMix64;
+    use crate::{cage::Cage, trace::SolveStats};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    thread_local! {
+        static ALLOCS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// count the allocations of each thread, so the tests running beside do not count.
+    struct Counting;
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCS.try_with(|allocs| allocs.set(allocs.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static COUNTING: Counting = Counting;
+
+    fn allocs() -> usize {
+        ALLOCS.with(Cell::get)
+    }
 
     #[test]
     fn solve() {
@@ -121,6 +571,102 @@ mod test {
         assert_eq!(solver.unique(&mut board).unwrap(), false);
     }
 
+    #[test]
+    fn jigsaw() {
+        let solved = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        // (0, 2) and (1, 3) both hold 3, so they can swap their regions
+        let mut board: Board = "regions: 112222333111122333111222333444555666444555666444555666777888999777888999777888999"
+            .parse()
+            .unwrap();
+        for pos in 0..81 {
+            if pos % 3 != 0 {
+                let val = solved.get(pos / 9, pos % 9).unwrap().unwrap();
+                board.set(pos / 9, pos % 9, val).unwrap();
+            }
+        }
+        let solver = DfsSolver::new();
+        assert!(solver.unique(&mut board.clone()).unwrap());
+        assert!(solver.solve(&mut board).is_ok());
+        assert!(board.is_win());
+        assert_eq!(board.get(1, 3).unwrap(), Some(3));
+    }
+
+    #[test]
+    fn killer() {
+        let solved = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        // dominoes along every row, no given at all
+        let mut board = Board::new();
+        for x in 0..9 {
+            for y in (0..9).step_by(2) {
+                let slots: Vec<_> = (y..9.min(y + 2)).map(|y| (x, y)).collect();
+                let sum = slots
+                    .iter()
+                    .map(|&(x, y)| solved.get(x, y).unwrap().unwrap())
+                    .sum();
+                board.add_cage(Cage::new(sum, slots).unwrap()).unwrap();
+            }
+        }
+        let solver = DfsSolver::new();
+        assert!(solver.solve(&mut board).is_ok());
+        assert!(board.is_win());
+    }
+
+    #[test]
+    fn budget_exhausted() {
+        let board = Board::from_vec(vec![
+            0, 0, 0, 2, 0, 8, 7, 0, 9, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 3, 0, 0, 0, 0, 8,
+            7, 0, 0, 4, 3, 0, 5, 6, 0, 0, 0, 5, 9, 0, 0, 1, 1, 9, 0, 3, 0, 2, 0, 0, 0, 9, 0, 8, 5,
+            2, 6, 1, 0, 3, 5, 1, 6, 4, 3, 7, 9, 2, 8, 4, 2, 0, 8, 0, 0, 6, 5, 7,
+        ]);
+        let solver = DfsSolver::with_budget(Budget::new().max_nodes(3));
+        assert!(matches!(
+            solver.solve(&mut board.clone()),
+            Err(SuDoKuError::BudgetExhausted)
+        ));
+        assert!(matches!(
+            solver.unique(&mut board.clone()),
+            Err(SuDoKuError::BudgetExhausted)
+        ));
+        // the budget is for every search, not for the solver
+        let solver = DfsSolver::with_budget(Budget::new().max_nodes(10_000));
+        assert!(solver.solve(&mut board.clone()).is_ok());
+        assert!(solver.unique(&mut board.clone()).unwrap());
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn cancelled() {
+        let token = crate::budget::CancelToken::new();
+        token.cancel();
+        let solver = DfsSolver::with_budget(Budget::new().cancel_token(token));
+        assert!(matches!(
+            solver.solve(&mut Board::new()),
+            Err(SuDoKuError::BudgetExhausted)
+        ));
+    }
+
+    #[test]
+    fn solve_stats() {
+        let mut board = Board::from_vec(vec![
+            0, 0, 0, 2, 0, 8, 7, 0, 9, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 3, 0, 0, 0, 0, 8,
+            7, 0, 0, 4, 3, 0, 5, 6, 0, 0, 0, 5, 9, 0, 0, 1, 1, 9, 0, 3, 0, 2, 0, 0, 0, 9, 0, 8, 5,
+            2, 6, 1, 0, 3, 5, 1, 6, 4, 3, 7, 9, 2, 8, 4, 2, 0, 8, 0, 0, 6, 5, 7,
+        ]);
+        let mut stats = SolveStats::new();
+        let path = DfsSolver::new().solve_with(&mut board, &mut stats).unwrap();
+        assert_eq!(stats.max_depth, path.len());
+        assert!(stats.nodes >= path.len());
+        assert!(stats.backtracks > 0);
+    }
+
     #[test]
     fn unique() {
         let mut board = Board::from_vec(vec![
@@ -132,6 +678,131 @@ mod test {
         assert_eq!(solver.unique(&mut board).unwrap(), true);
     }
 
+    #[test]
+    fn unique_solved() {
+        let mut board = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        assert!(DfsSolver::new().unique(&mut board).unwrap());
+    }
+
+    #[test]
+    fn solve_random() {
+        let solver = DfsSolver::new();
+        let mut seen = Vec::new();
+        for seed in 0..3 {
+            let mut board = Board::new();
+            board.set(4, 4, 5).unwrap();
+            solver
+                .solve_random(&mut board, &mut SplitMix64::new(seed))
+                .unwrap();
+            assert!(board.is_win());
+            assert_eq!(board.unchecked_get(4, 4), Some(5));
+            seen.push(board.to_string());
+        }
+        assert_ne!(seen[0], seen[1]);
+        assert_ne!(seen[1], seen[2]);
+        let mut again = Board::new();
+        again.set(4, 4, 5).unwrap();
+        solver
+            .solve_random(&mut again, &mut SplitMix64::new(2))
+            .unwrap();
+        assert_eq!(again.to_string(), seen[2]);
+    }
+
+    #[test]
+    fn solutions() {
+        let solved = Board::from_vec(vec![
+            9, 5, 3, 1, 2, 4, 7, 6, 8, 2, 4, 6, 3, 7, 8, 1, 5, 9, 7, 8, 1, 6, 5, 9, 2, 3, 4, 8, 6,
+            9, 7, 1, 3, 5, 4, 2, 3, 2, 4, 8, 6, 5, 9, 1, 7, 1, 7, 5, 9, 4, 2, 6, 8, 3, 5, 9, 8, 2,
+            3, 6, 4, 7, 1, 4, 1, 2, 5, 8, 7, 3, 9, 6, 6, 3, 7, 4, 9, 1, 8, 2, 5,
+        ]);
+        let solver = DfsSolver::new();
+        assert_eq!(solver.solutions(&solved).count(), 1);
+        // the last band has more than one order
+        let mut board = solved.clone();
+        for pos in 54..81 {
+            board.unset(pos / 9, pos % 9);
+        }
+        let grids: Vec<Board> = solver.solutions(&board).map(Result::unwrap).collect();
+        assert!(grids.len() > 1);
+        assert!(grids
+            .iter()
+            .any(|grid| grid.to_string() == solved.to_string()));
+        for (i, grid) in grids.iter().enumerate() {
+            assert!(grid.is_win());
+            assert!(grids[..i]
+                .iter()
+                .all(|other| other.to_string() != grid.to_string()));
+        }
+        // an error ends the walk
+        let solver = DfsSolver::with_budget(Budget::new().max_nodes(3));
+        let mut walk = solver.solutions(&board);
+        assert!(matches!(
+            walk.next(),
+            Some(Err(SuDoKuError::BudgetExhausted))
+        ));
+        assert!(walk.next().is_none());
+    }
+
+    #[test]
+    fn fixed() {
+        let board = Board::from_vec(vec![
+            0, 0, 0, 2, 0, 8, 7, 0, 9, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 3, 0, 0, 0, 0, 8,
+            7, 0, 0, 4, 3, 0, 5, 6, 0, 0, 0, 5, 9, 0, 0, 1, 1, 9, 0, 3, 0, 2, 0, 0, 0, 9, 0, 8, 5,
+            2, 6, 1, 0, 3, 5, 1, 6, 4, 3, 7, 9, 2, 8, 4, 2, 0, 8, 0, 0, 6, 5, 7,
+        ]);
+        let solver = DfsSolver::new();
+        let mut fixed = board.clone();
+        solver.solve_fixed(&mut fixed).unwrap();
+        let mut solved = board.clone();
+        solver.solve(&mut solved).unwrap();
+        assert_eq!(fixed.to_string(), solved.to_string());
+        assert!(solver.unique_fixed(&mut board.clone()).unwrap());
+        assert!(solver.unique_fixed(&mut fixed).unwrap());
+        let mut board = Board::new();
+        board.set(0, 0, 1).unwrap();
+        board.set(0, 1, 2).unwrap();
+        assert!(!solver.unique_fixed(&mut board.clone()).unwrap());
+        assert!(matches!(
+            DfsSolver::with_budget(Budget::new().max_nodes(3)).solve_fixed(&mut board),
+            Err(SuDoKuError::BudgetExhausted)
+        ));
+    }
+
+    #[test]
+    fn fixed_without_alloc() {
+        let mut board = Board::from_vec(vec![
+            0, 0, 0, 2, 0, 8, 7, 0, 9, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 3, 0, 0, 0, 0, 8,
+            7, 0, 0, 4, 3, 0, 5, 6, 0, 0, 0, 5, 9, 0, 0, 1, 1, 9, 0, 3, 0, 2, 0, 0, 0, 9, 0, 8, 5,
+            2, 6, 1, 0, 3, 5, 1, 6, 4, 3, 7, 9, 2, 8, 4, 2, 0, 8, 0, 0, 6, 5, 7,
+        ]);
+        let solver = DfsSolver::with_budget(Budget::new().max_nodes(100_000));
+        let before = allocs();
+        assert!(solver.unique_fixed(&mut board).unwrap());
+        solver.solve_fixed(&mut board).unwrap();
+        assert_eq!(allocs(), before);
+        assert!(board.is_win());
+    }
+
+    #[test]
+    fn hint() {
+        let board = Board::from_vec(vec![
+            0, 0, 0, 2, 0, 8, 7, 0, 9, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 3, 0, 0, 0, 0, 8,
+            7, 0, 0, 4, 3, 0, 5, 6, 0, 0, 0, 5, 9, 0, 0, 1, 1, 9, 0, 3, 0, 2, 0, 0, 0, 9, 0, 8, 5,
+            2, 6, 1, 0, 3, 5, 1, 6, 4, 3, 7, 9, 2, 8, 4, 2, 0, 8, 0, 0, 6, 5, 7,
+        ]);
+        let solver = DfsSolver::new();
+        let (x, y, val) = solver.hint(&board).unwrap().unwrap();
+        assert_eq!(board.avaliable_count(x, y), 1);
+        let mut solved = board.clone();
+        solver.solve(&mut solved).unwrap();
+        assert_eq!(solved.get(x, y).unwrap(), Some(val));
+        assert!(solver.hint(&solved).unwrap().is_none());
+    }
+
     #[test]
     fn unique_idempotence() {
         let board = Board::from_vec(vec![
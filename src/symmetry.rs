@@ -0,0 +1,298 @@
+//! the symmetries of a classic sudoku, and a canonical form under them.
+//!
+//! two puzzles are the same puzzle if one turns into the other by relabeling values,
+//! transposing, swapping rows in a band, columns in a stack, bands or stacks.
+//! the canonical form is the smallest of all of them read row by row, empty as 0.
+//! ```
+//! use sudoku_rs::{board::Board, symmetry};
+//!
+//! let a: Board = "200007030800000009701005048000400001000500273010379006070030000000002590600000000".parse().unwrap();
+//! // values 1 and 2 swapped
+//! let b: Board = "100007030800000009702005048000400002000500173020379006070030000000001590600000000".parse().unwrap();
+//! assert_eq!(symmetry::minlex(&a).unwrap(), symmetry::minlex(&b).unwrap());
+//! ```
+use crate::{board::Board, error::SuDoKuError, prelude::*, rng::Rng};
+use core::cmp::Ordering;
+
+const PERMS: [[u8; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// a partial transform, every transform with the same state gives the same rows from here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct State {
+    transposed: bool,
+    /// the source rows used.
+    used: u16,
+    /// the source band of the last row.
+    band: u8,
+    /// the source column of every column.
+    cols: [u8; 9],
+    /// the new label of every value, 0 for not met yet.
+    map: [u8; 10],
+    next: u8,
+}
+
+impl State {
+    fn relabel(&mut self, row: &[u8]) -> [u8; 9] {
+        let mut line = [0; 9];
+        for (cell, &col) in line.iter_mut().zip(self.cols.iter()) {
+            let val = row[col as usize] as usize;
+            if val != 0 {
+                if self.map[val] == 0 {
+                    self.map[val] = self.next;
+                    self.next += 1;
+                }
+                *cell = self.map[val];
+            }
+        }
+        line
+    }
+}
+
+/// every order of columns which keeps stacks together.
+fn column_orders() -> Vec<[u8; 9]> {
+    let mut orders = Vec::with_capacity(1296);
+    for stacks in PERMS.iter() {
+        for a in PERMS.iter() {
+            for b in PERMS.iter() {
+                for c in PERMS.iter() {
+                    let mut cols = [0; 9];
+                    for (i, inner) in [a, b, c].iter().enumerate() {
+                        for j in 0..3 {
+                            cols[i * 3 + j] = stacks[i] * 3 + inner[j];
+                        }
+                    }
+                    orders.push(cols);
+                }
+            }
+        }
+    }
+    orders
+}
+
+/// keep the states giving the smallest line.
+fn keep(states: &mut Vec<State>, best: &mut [u8; 9], state: State, line: [u8; 9]) {
+    match line.cmp(best) {
+        Ordering::Less => {
+            states.clear();
+            *best = line;
+            states.push(state);
+        }
+        Ordering::Equal => states.push(state),
+        Ordering::Greater => {}
+    }
+}
+
+/// the canonical form of a classic board, 81 values row by row, 0 for empty.
+/// fail with `SuDoKuError::InvalidFormat` if the board is not classic.
+///
+/// rows are fixed one by one, only the transforms giving the smallest rows so far are kept.
+/// a board with many symmetries of its own keeps more of them and is slower.
+pub fn minlex(board: &Board) -> Result<[u8; 81], SuDoKuError> {
+    // symmetries of other rules differ
+    if !board.is_classic() {
+        return Err(SuDoKuError::InvalidFormat);
+    }
+    let mut grids = [[0; 81]; 2];
+    for pos in 0..81 {
+        let val = board.unchecked_get(pos / 9, pos % 9).unwrap_or(0);
+        grids[0][pos] = val;
+        grids[1][pos % 9 * 9 + pos / 9] = val;
+    }
+    let mut form = [0; 81];
+    let mut states = Vec::new();
+    let mut best = [u8::MAX; 9];
+    // the first row fixes the order of columns
+    for cols in column_orders() {
+        for (transposed, grid) in grids.iter().enumerate() {
+            for row in 0..9 {
+                let mut state = State {
+                    transposed: transposed == 1,
+                    used: 1 << row,
+                    band: row as u8 / 3,
+                    cols,
+                    map: [0; 10],
+                    next: 1,
+                };
+                let line = state.relabel(&grid[row * 9..row * 9 + 9]);
+                keep(&mut states, &mut best, state, line);
+            }
+        }
+    }
+    form[..9].copy_from_slice(&best);
+    for out in 1..9 {
+        let mut next = Vec::new();
+        let mut best = [u8::MAX; 9];
+        for state in states.iter() {
+            let grid = &grids[state.transposed as usize];
+            for row in 0..9 {
+                let band = row as u8 / 3;
+                let free = state.used & (1 << row) == 0;
+                // a new band starts every third row, else the band goes on
+                let allowed = if out % 3 == 0 {
+                    state.used & (0b111 << (band * 3)) == 0
+                } else {
+                    band == state.band
+                };
+                if free && allowed {
+                    let mut state = *state;
+                    state.used |= 1 << row;
+                    state.band = band;
+                    let line = state.relabel(&grid[row * 9..row * 9 + 9]);
+                    keep(&mut next, &mut best, state, line);
+                }
+            }
+        }
+        // transforms differing only in the order of used rows are one
+        next.sort_unstable();
+        next.dedup();
+        form[out * 9..out * 9 + 9].copy_from_slice(&best);
+        states = next;
+    }
+    Ok(form)
+}
+
+/// a 64 bit fnv-1a hash of the canonical form, the same for every platform and version.
+/// fail with `SuDoKuError::InvalidFormat` if the board is not classic.
+pub fn canonical_hash(board: &Board) -> Result<u64, SuDoKuError> {
+    Ok(hash(&minlex(board)?))
+}
+
+/// the hash of a canonical form, see `canonical_hash`.
+pub fn hash(form: &[u8; 81]) -> u64 {
+    form.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &val| {
+        (hash ^ u64::from(val)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// a random symmetry of a classic board: values relabeled, maybe transposed, then bands,
+/// stacks, rows in a band and columns in a stack reordered. the canonical form is kept.
+/// fail with `SuDoKuError::InvalidFormat` if the board is not classic.
+/// ```
+/// use sudoku_rs::{board::Board, rng::SplitMix64, symmetry};
+///
+/// let board: Board = "200007030800000009701005048000400001000500273010379006070030000000002590600000000".parse().unwrap();
+/// let turned = symmetry::random_transform(&board, &mut SplitMix64::new(9)).unwrap();
+/// assert_eq!(symmetry::minlex(&turned).unwrap(), symmetry::minlex(&board).unwrap());
+/// ```
+pub fn random_transform<R: Rng>(board: &Board, rng: &mut R) -> Result<Board, SuDoKuError> {
+    if !board.is_classic() {
+        return Err(SuDoKuError::InvalidFormat);
+    }
+    let mut labels: Vec<u8> = (0..10).collect();
+    rng.shuffle(&mut labels[1..]);
+    let transposed = rng.below(2) == 1;
+    let rows = random_order(rng);
+    let cols = random_order(rng);
+    let mut cells = vec![0; 81];
+    for (pos, cell) in cells.iter_mut().enumerate() {
+        let (x, y) = (rows[pos / 9], cols[pos % 9]);
+        let (x, y) = if transposed { (y, x) } else { (x, y) };
+        *cell = labels[board.unchecked_get(x, y).unwrap_or(0) as usize];
+    }
+    Ok(Board::from_vec(cells))
+}
+
+/// a random order of the nine rows, or columns, which keeps bands together.
+fn random_order<R: Rng>(rng: &mut R) -> [usize; 9] {
+    let mut bands = [0, 1, 2];
+    rng.shuffle(&mut bands);
+    let mut order = [0; 9];
+    for (i, band) in bands.iter().enumerate() {
+        let mut inner = [0, 1, 2];
+        rng.shuffle(&mut inner);
+        for (j, row) in inner.iter().enumerate() {
+            order[i * 3 + j] = band * 3 + row;
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SplitMix64;
+
+    const EASY: &str =
+        "200007030800000009701005048000400001000500273010379006070030000000002590600000000";
+    const HARD: &str =
+        "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+
+    /// transpose, swap the first two bands, the last two columns and relabel `v` to `10 - v`.
+    fn shuffled(board: &Board) -> Board {
+        let mut cells = vec![0; 81];
+        for x in 0..9 {
+            for y in 0..9 {
+                if let Some(val) = board.unchecked_get(x, y) {
+                    let (x, y) = (y, x);
+                    let x = match x / 3 {
+                        0 => x + 3,
+                        1 => x - 3,
+                        _ => x,
+                    };
+                    let y = match y {
+                        7 => 8,
+                        8 => 7,
+                        y => y,
+                    };
+                    cells[x * 9 + y] = 10 - val;
+                }
+            }
+        }
+        Board::from_vec(cells)
+    }
+
+    #[test]
+    fn invariant() {
+        for text in [EASY, HARD].iter() {
+            let board: Board = text.parse().unwrap();
+            let form = minlex(&board).unwrap();
+            assert_eq!(minlex(&shuffled(&board)).unwrap(), form);
+            assert_eq!(
+                canonical_hash(&shuffled(&board)).unwrap(),
+                canonical_hash(&board).unwrap()
+            );
+            // empty slots first
+            assert_eq!(form[0], 0);
+            let clues = (0..81).filter(|&pos| !board.is_empty(pos / 9, pos % 9));
+            assert_eq!(form.iter().filter(|&&val| val != 0).count(), clues.count());
+        }
+        let easy: Board = EASY.parse().unwrap();
+        let hard: Board = HARD.parse().unwrap();
+        assert_ne!(minlex(&easy).unwrap(), minlex(&hard).unwrap());
+    }
+
+    #[test]
+    fn random_transforms() {
+        let board: Board = HARD.parse().unwrap();
+        let form = minlex(&board).unwrap();
+        let mut rng = SplitMix64::new(13);
+        let mut seen = Vec::new();
+        for _ in 0..10 {
+            let turned = random_transform(&board, &mut rng).unwrap();
+            assert_eq!(minlex(&turned).unwrap(), form);
+            seen.push(turned.to_string());
+        }
+        seen.sort_unstable();
+        seen.dedup();
+        assert!(seen.len() > 1);
+        assert!(random_transform(&Board::new_diagonal(), &mut rng).is_err());
+    }
+
+    #[test]
+    fn symmetric_boards() {
+        assert_eq!(minlex(&Board::new()).unwrap(), [0; 81]);
+        let mut one = Board::new();
+        one.unchecked_set(4, 4, 7);
+        let mut form = [0; 81];
+        form[80] = 1;
+        assert_eq!(minlex(&one).unwrap(), form);
+        assert!(minlex(&Board::new_diagonal()).is_err());
+    }
+}
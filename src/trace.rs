@@ -0,0 +1,194 @@
+//! search statistics and tracing hooks.
+//!
+//! every search in this crate reports what it does to an [`Observer`].
+//! [`SolveStats`] is an observer which counts the events.
+//! ```
+//! use sudoku_rs::{board::Board, solver::DfsSolver, trace::SolveStats};
+//!
+//! let sudoku_str = "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+//! let mut b: Board = sudoku_str.parse().unwrap();
+//! let mut stats = SolveStats::new();
+//! DfsSolver::new().solve_with(&mut b, &mut stats).unwrap();
+//! println!("{:?}", stats);
+//! ```
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// receive the events of a search.
+///
+/// `depth` is the count of slots filled by the search, include the current one.
+/// every value put on the board is either a `guess` or a `place`.
+pub trait Observer {
+    /// the search begin.
+    fn start(&mut self) {}
+
+    /// put a value on a slot which have more than one avaliable value.
+    fn guess(&mut self, _x: usize, _y: usize, _val: u8, _depth: usize) {}
+
+    /// put the only avaliable value on a slot.
+    fn place(&mut self, _x: usize, _y: usize, _val: u8, _depth: usize) {}
+
+    /// no avaliable value left for a slot, clear it and go back.
+    fn backtrack(&mut self, _x: usize, _y: usize, _depth: usize) {}
+
+    /// the search end, no matter solved or not.
+    fn finish(&mut self) {}
+}
+
+/// observer which ignores everything.
+impl Observer for () {}
+
+impl<O: Observer + ?Sized> Observer for &mut O {
+    fn start(&mut self) {
+        (**self).start()
+    }
+
+    fn guess(&mut self, x: usize, y: usize, val: u8, depth: usize) {
+        (**self).guess(x, y, val, depth)
+    }
+
+    fn place(&mut self, x: usize, y: usize, val: u8, depth: usize) {
+        (**self).place(x, y, val, depth)
+    }
+
+    fn backtrack(&mut self, x: usize, y: usize, depth: usize) {
+        (**self).backtrack(x, y, depth)
+    }
+
+    fn finish(&mut self) {
+        (**self).finish()
+    }
+}
+
+/// send every event to both observers.
+impl<A: Observer, B: Observer> Observer for (A, B) {
+    fn start(&mut self) {
+        self.0.start();
+        self.1.start();
+    }
+
+    fn guess(&mut self, x: usize, y: usize, val: u8, depth: usize) {
+        self.0.guess(x, y, val, depth);
+        self.1.guess(x, y, val, depth);
+    }
+
+    fn place(&mut self, x: usize, y: usize, val: u8, depth: usize) {
+        self.0.place(x, y, val, depth);
+        self.1.place(x, y, val, depth);
+    }
+
+    fn backtrack(&mut self, x: usize, y: usize, depth: usize) {
+        self.0.backtrack(x, y, depth);
+        self.1.backtrack(x, y, depth);
+    }
+
+    fn finish(&mut self) {
+        self.0.finish();
+        self.1.finish();
+    }
+}
+
+/// statistics of searches.
+/// counters add up when used for more than one search.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SolveStats {
+    /// values put on the board, guesses and propagations.
+    pub nodes: usize,
+    /// slots given up and cleared.
+    pub backtracks: usize,
+    /// the deepest the search ever went.
+    pub max_depth: usize,
+    /// values put on a slot which have only one avaliable value.
+    pub propagations: usize,
+    /// time spent between `start` and `finish`, always zero without `std`.
+    pub elapsed: Duration,
+    #[cfg(feature = "std")]
+    started: Option<Instant>,
+}
+
+impl SolveStats {
+    /// return a new empty stats.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// values put on a slot which have more than one avaliable value.
+    pub fn guesses(&self) -> usize {
+        self.nodes - self.propagations
+    }
+
+    fn visit(&mut self, depth: usize) {
+        self.nodes += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+}
+
+impl Observer for SolveStats {
+    #[cfg(feature = "std")]
+    fn start(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    fn guess(&mut self, _x: usize, _y: usize, _val: u8, depth: usize) {
+        self.visit(depth);
+    }
+
+    fn place(&mut self, _x: usize, _y: usize, _val: u8, depth: usize) {
+        self.visit(depth);
+        self.propagations += 1;
+    }
+
+    fn backtrack(&mut self, _x: usize, _y: usize, _depth: usize) {
+        self.backtracks += 1;
+    }
+
+    #[cfg(feature = "std")]
+    fn finish(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.elapsed += started.elapsed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    impl Observer for Log {
+        fn guess(&mut self, _x: usize, _y: usize, _val: u8, _depth: usize) {
+            self.0.push("guess");
+        }
+
+        fn backtrack(&mut self, _x: usize, _y: usize, _depth: usize) {
+            self.0.push("backtrack");
+        }
+    }
+
+    #[test]
+    fn stats_count() {
+        let mut stats = SolveStats::new();
+        stats.start();
+        stats.guess(0, 0, 1, 1);
+        stats.place(0, 1, 2, 2);
+        stats.backtrack(0, 1, 2);
+        stats.finish();
+        assert_eq!(stats.nodes, 2);
+        assert_eq!(stats.propagations, 1);
+        assert_eq!(stats.guesses(), 1);
+        assert_eq!(stats.backtracks, 1);
+        assert_eq!(stats.max_depth, 2);
+    }
+
+    #[test]
+    fn pair() {
+        let mut pair = (SolveStats::new(), Log::default());
+        pair.guess(0, 0, 1, 1);
+        pair.backtrack(0, 0, 1);
+        assert_eq!(pair.0.nodes, 1);
+        assert_eq!(pair.1 .0, vec!["guess", "backtrack"]);
+    }
+}
@@ -0,0 +1,209 @@
+//! javascript bindings, built with the `wasm` feature.
+//!
+//! a board is a `Board` class on the js side. slots are plain `Uint8Array`s of 81 values,
+//! 0 for empty, and scores and hints are plain objects.
+//! ```js
+//! import { Board, generate } from "sudoku_rs";
+//!
+//! const board = Board.parse("400000805030000000000700000020000060000080400000010000000603070500200000104000000");
+//! board.unique(); // true
+//! board.grade(); // { dfsComplexity: 1234, emptySlotCount: 64 }
+//! board.hint(); // { x: 0, y: 1, value: 1 }
+//! board.solve();
+//! board.cells(); // Uint8Array(81)
+//! generate(42, "killer").toString();
+//! ```
+//! every search takes an optional node budget, so a bad puzzle can not hang the page.
+//!
+//! build it with
+//! `cargo rustc --release --lib --crate-type cdylib --features wasm --target wasm32-unknown-unknown`
+//! and then run `wasm-bindgen` on the `.wasm` file.
+use crate::{
+    board::Board,
+    budget::Budget,
+    error::SuDoKuError,
+    generator::{Generator, JigsawOptions, KillerOptions},
+    grade::Grade,
+    prelude::*,
+    solver::DfsSolver,
+};
+use js_sys::{Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+fn budget(max_nodes: Option<usize>) -> Budget {
+    match max_nodes {
+        Some(nodes) => Budget::new().max_nodes(nodes),
+        None => Budget::new(),
+    }
+}
+
+/// a slot out of bound is an error for js, not a silent no-op.
+fn check_bound(x: usize, y: usize) -> Result<(), SuDoKuError> {
+    if x >= 9 || y >= 9 {
+        return Err(SuDoKuError::OutOfBound);
+    }
+    Ok(())
+}
+
+/// a plain object of number fields.
+fn object(fields: &[(&str, usize)]) -> JsValue {
+    let object = Object::new();
+    for &(key, val) in fields {
+        Reflect::set(&object, &key.into(), &(val as f64).into())
+            .expect("object: set a field of a plain object");
+    }
+    object.into()
+}
+
+/// a sudoku board.
+#[wasm_bindgen(js_name = Board)]
+#[derive(Clone, Default)]
+pub struct WasmBoard {
+    board: Board,
+}
+
+#[wasm_bindgen(js_class = Board)]
+impl WasmBoard {
+    /// return an empty classic board.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmBoard {
+        Self::default()
+    }
+
+    /// parse a board in the text format of `Board`, variants included.
+    pub fn parse(text: &str) -> Result<WasmBoard, JsError> {
+        Ok(WasmBoard {
+            board: text.parse()?,
+        })
+    }
+
+    /// a classic board from 81 values, 0 for empty.
+    #[wasm_bindgen(js_name = fromCells)]
+    pub fn from_cells(cells: &[u8]) -> Result<WasmBoard, JsError> {
+        if cells.len() != 81 {
+            return Err(SuDoKuError::InvalidFormat.into());
+        }
+        let mut board = Board::new();
+        for (pos, &val) in cells.iter().enumerate() {
+            if val != 0 {
+                board.set(pos / 9, pos % 9, val)?;
+            }
+        }
+        Ok(WasmBoard { board })
+    }
+
+    /// the 81 values, 0 for empty.
+    pub fn cells(&self) -> Vec<u8> {
+        (0..81)
+            .map(|pos| self.board.unchecked_get(pos / 9, pos % 9).unwrap_or(0))
+            .collect()
+    }
+
+    /// the value of a slot, `undefined` for empty.
+    pub fn get(&self, x: usize, y: usize) -> Result<Option<u8>, JsError> {
+        Ok(self.board.get(x, y)?)
+    }
+
+    /// put a value on a slot.
+    pub fn set(&mut self, x: usize, y: usize, val: u8) -> Result<(), JsError> {
+        Ok(self.board.set(x, y, val)?)
+    }
+
+    /// clear a slot.
+    pub fn unset(&mut self, x: usize, y: usize) -> Result<(), JsError> {
+        check_bound(x, y)?;
+        self.board.unset(x, y);
+        Ok(())
+    }
+
+    /// the values which can be put on a slot.
+    pub fn candidates(&self, x: usize, y: usize) -> Result<Vec<u8>, JsError> {
+        check_bound(x, y)?;
+        Ok(self.board.avaliable_val(x, y))
+    }
+
+    /// check if the board is filled without conflict.
+    #[wasm_bindgen(js_name = isWin)]
+    pub fn is_win(&self) -> bool {
+        self.board.is_win()
+    }
+
+    /// the board in the text format.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn text(&self) -> String {
+        self.board.to_string()
+    }
+
+    /// solve the board in place.
+    pub fn solve(&mut self, max_nodes: Option<usize>) -> Result<(), JsError> {
+        DfsSolver::with_budget(budget(max_nodes)).solve(&mut self.board)?;
+        Ok(())
+    }
+
+    /// check if the solve is unique, the board is kept as it is.
+    pub fn unique(&self, max_nodes: Option<usize>) -> Result<bool, JsError> {
+        Ok(DfsSolver::with_budget(budget(max_nodes)).unique(&mut self.board.clone())?)
+    }
+
+    /// grade the board, `{ dfsComplexity, emptySlotCount }`.
+    pub fn grade(&self, max_nodes: Option<usize>) -> Result<JsValue, JsError> {
+        let score = Grade::with_budget(budget(max_nodes)).try_grade(&mut self.board.clone())?;
+        Ok(object(&[
+            ("dfsComplexity", score.dfs_complexity),
+            ("emptySlotCount", score.empty_slot_count),
+        ]))
+    }
+
+    /// a value to fill next, `{ x, y, value }`, `undefined` if the board is full.
+    pub fn hint(&self, max_nodes: Option<usize>) -> Result<JsValue, JsError> {
+        let hint = DfsSolver::with_budget(budget(max_nodes)).hint(&self.board)?;
+        Ok(match hint {
+            Some((x, y, val)) => object(&[("x", x), ("y", y), ("value", val as usize)]),
+            None => JsValue::UNDEFINED,
+        })
+    }
+}
+
+/// generate a puzzle with a unique solve, reproducible with the same seed.
+/// `variant` is one of `classic`, the default, `diagonal`, `killer` and `jigsaw`.
+#[wasm_bindgen]
+pub fn generate(seed: u32, variant: Option<String>) -> Result<WasmBoard, JsError> {
+    let mut generator = Generator::new(seed as u64);
+    let board = match variant.as_deref().unwrap_or("classic") {
+        "classic" => generator.generate(&Board::new())?,
+        "diagonal" => generator.generate(&Board::new_diagonal())?,
+        "killer" => generator.killer(&KillerOptions::new())?,
+        "jigsaw" => generator.jigsaw(&JigsawOptions::new())?,
+        _ => return Err(SuDoKuError::InvalidFormat.into()),
+    };
+    Ok(WasmBoard { board })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HARD: &str =
+        "400000805030000000000700000020000060000080400000010000000603070500200000104000000";
+
+    #[test]
+    fn cells() {
+        let parsed = WasmBoard::parse(HARD).unwrap();
+        let cells = parsed.cells();
+        assert_eq!(cells.len(), 81);
+        let mut board = WasmBoard::from_cells(&cells).unwrap();
+        assert_eq!(board.text(), parsed.text());
+        assert!(board.unique(None).unwrap());
+        board.solve(None).unwrap();
+        assert!(board.is_win());
+        assert!(board.cells().iter().all(|&val| val != 0));
+    }
+
+    #[test]
+    fn generate_variants() {
+        for variant in ["classic", "diagonal"].iter() {
+            let board = generate(7, Some(variant.to_string())).unwrap();
+            assert!(board.unique(None).unwrap());
+        }
+    }
+}